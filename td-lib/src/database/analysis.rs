@@ -0,0 +1,296 @@
+//! Analysis of the task graph, such as finding the tasks that unblock the most work.
+
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use petgraph::{algo::toposort, stable_graph::NodeIndex, Direction};
+
+use super::{Database, Priority, Task, TaskId};
+use crate::errors::DependencyCycleError;
+
+/// An order to sort tasks in. See [`Database::sort_tasks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TaskOrder {
+    /// Keep the existing order.
+    #[default]
+    Unchanged,
+    /// Tasks that are due first, followed by tasks without a due date.
+    DueDate,
+    /// The most important tasks first, followed by tasks without a priority.
+    Priority,
+    /// The tasks with the highest [effective priority](Database::effective_priority) first.
+    EffectivePriority,
+    /// The tasks that the most unfinished tasks (indirectly) depend on first.
+    UnblockedWork,
+    /// The tasks on the [critical path](Database::critical_path) first, in the order they should
+    /// be completed.
+    CriticalPath,
+}
+
+impl TaskOrder {
+    /// Every order, in the order they should be presented to the user.
+    pub const ALL: [Self; 6] = [
+        Self::Unchanged,
+        Self::DueDate,
+        Self::Priority,
+        Self::EffectivePriority,
+        Self::UnblockedWork,
+        Self::CriticalPath,
+    ];
+}
+
+impl Display for TaskOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Unchanged => "creation date",
+            Self::DueDate => "due date",
+            Self::Priority => "priority",
+            Self::EffectivePriority => "effective priority",
+            Self::UnblockedWork => "unblocked work",
+            Self::CriticalPath => "critical path",
+        })
+    }
+}
+
+impl Database {
+    /// Gets all tasks in an order where every task comes after the tasks it depends on.
+    ///
+    /// Returns an error if the graph contains a dependency cycle, which is only possible in
+    /// databases created before cycles were rejected.
+    pub fn topological_order(&self) -> Result<Vec<&Task>, DependencyCycleError> {
+        Ok(self
+            .topological_indices()?
+            .into_iter()
+            .map(|index| &self.graph[index])
+            .collect())
+    }
+
+    /// Gets the longest chain of unfinished tasks where each task depends on the previous one.
+    /// These tasks can only be completed one after another, so this is the least amount of work
+    /// that is left before the last task in the chain can be completed.
+    ///
+    /// Returns an error if the graph contains a dependency cycle, like
+    /// [`Self::topological_order`].
+    pub fn critical_path(&self) -> Result<Vec<&Task>, DependencyCycleError> {
+        // for every task, the length of the longest chain that ends at it and the previous task in
+        // that chain
+        let mut chains = HashMap::<NodeIndex, (usize, Option<NodeIndex>)>::new();
+        let mut last = None;
+        for index in self.topological_indices()? {
+            if self.graph[index].time_completed.is_some() {
+                continue;
+            }
+
+            let previous = self
+                .graph
+                .neighbors_directed(index, Direction::Outgoing)
+                .filter_map(|dependency| Some((chains.get(&dependency)?.0, dependency)))
+                .max_by_key(|&(length, dependency)| (length, Reverse(dependency)));
+            let length = previous.map_or(1, |(length, _)| length + 1);
+            chains.insert(index, (length, previous.map(|(_, dependency)| dependency)));
+
+            if last.is_none_or(|(last_length, _)| length > last_length) {
+                last = Some((length, index));
+            }
+        }
+
+        let mut path = vec![];
+        let mut current = last.map(|(_, index)| index);
+        while let Some(index) = current {
+            path.push(&self.graph[index]);
+            current = chains[&index].1;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Gets the unfinished tasks that (indirectly) depend on the given task. These tasks can not be
+    /// completed before the given task is. Completed tasks are not followed, since they no longer
+    /// wait on their dependencies.
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn get_transitive_dependents(&self, task_id: &TaskId) -> Vec<&Task> {
+        self.get_transitive_neighbors(task_id, Direction::Incoming)
+    }
+
+    /// Gets the unfinished tasks that the given task (indirectly) depends on. These tasks have to
+    /// be completed before the given task can be. Completed tasks are not followed, like in
+    /// [`Self::get_transitive_dependents`].
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn get_transitive_dependencies(&self, task_id: &TaskId) -> Vec<&Task> {
+        self.get_transitive_neighbors(task_id, Direction::Outgoing)
+    }
+
+    /// Gets the highest priority of the given task and the tasks that (indirectly) depend on it. A
+    /// task blocks everything that depends on it, so it is at least as important as those tasks.
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn effective_priority(&self, task_id: &TaskId) -> Option<Priority> {
+        self.get_transitive_dependents(task_id)
+            .into_iter()
+            .map(|task| task.priority)
+            .fold(self[task_id].priority, Option::max)
+    }
+
+    /// Sorts tasks in the given order. The sort is stable, so tasks that are equal in this order
+    /// keep their previous order.
+    ///
+    /// Panics if any of the tasks is not in this database.
+    pub fn sort_tasks<T: Borrow<Task>>(&self, tasks: &mut [T], order: TaskOrder) {
+        match order {
+            TaskOrder::Unchanged => (),
+            TaskOrder::DueDate => tasks.sort_by_key(|task| {
+                let due_date = task.borrow().due_date;
+                (due_date.is_none(), due_date)
+            }),
+            TaskOrder::Priority => tasks.sort_by_key(|task| Reverse(task.borrow().priority)),
+            TaskOrder::EffectivePriority => {
+                tasks.sort_by_cached_key(|task| {
+                    Reverse(self.effective_priority(task.borrow().id()))
+                });
+            }
+            TaskOrder::UnblockedWork => tasks.sort_by_cached_key(|task| {
+                Reverse(self.get_transitive_dependents(task.borrow().id()).len())
+            }),
+            TaskOrder::CriticalPath => {
+                // a cycle means there is no critical path, so the order is left alone
+                let path = self.critical_path().unwrap_or_default();
+                tasks.sort_by_cached_key(|task| {
+                    path.iter()
+                        .position(|t| t.id() == task.borrow().id())
+                        .unwrap_or(usize::MAX)
+                });
+            }
+        }
+    }
+
+    fn get_transitive_neighbors(&self, task_id: &TaskId, direction: Direction) -> Vec<&Task> {
+        let start_index = self
+            .get_node_index(task_id)
+            .expect("should be able to resolve task id");
+
+        let mut visited = HashSet::from([start_index]);
+        let mut queue = VecDeque::from([start_index]);
+        let mut neighbors = vec![];
+        while let Some(index) = queue.pop_front() {
+            for neighbor in self.graph.neighbors_directed(index, direction) {
+                let task = &self.graph[neighbor];
+                if task.time_completed.is_none() && visited.insert(neighbor) {
+                    neighbors.push(task);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn topological_indices(&self) -> Result<Vec<NodeIndex>, DependencyCycleError> {
+        // edges point from a task to its dependencies, so dependencies are sorted last
+        let mut order = toposort(&self.graph, None).map_err(|cycle| {
+            let index = cycle.node_id();
+            let task_id = &self.graph[index].id;
+            self.graph
+                .neighbors_directed(index, Direction::Outgoing)
+                .find_map(|next| self.find_dependency_cycle(task_id, &self.graph[next].id))
+                .map_or_else(
+                    || DependencyCycleError(vec![task_id.clone()]),
+                    DependencyCycleError,
+                )
+        })?;
+        order.reverse();
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{test_utils::add_tasks, TaskDependency};
+
+    fn ids<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Vec<TaskId> {
+        tasks.into_iter().map(|t| t.id().clone()).collect()
+    }
+
+    /// Creates the graph `0 -> 1 -> 2 -> 3` and `4 -> 3`, where `a -> b` means a depends on b.
+    fn create_graph() -> (Database, Vec<TaskId>) {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 5);
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[1], &ids[2]).unwrap();
+        db.add_dependency(&ids[2], &ids[3]).unwrap();
+        db.add_dependency(&ids[4], &ids[3]).unwrap();
+        (db, ids)
+    }
+
+    #[test]
+    fn topological_order() {
+        let (db, task_ids) = create_graph();
+        let order = ids(db.topological_order().unwrap());
+        let position = |i: usize| order.iter().position(|id| id == &task_ids[i]).unwrap();
+        assert!(position(3) < position(2));
+        assert!(position(2) < position(1));
+        assert!(position(1) < position(0));
+        assert!(position(3) < position(4));
+    }
+
+    #[test]
+    fn critical_path() {
+        let (mut db, task_ids) = create_graph();
+        assert_eq!(
+            ids(db.critical_path().unwrap()),
+            [3, 2, 1, 0].map(|i| task_ids[i].clone())
+        );
+
+        db[&task_ids[3]].time_completed = Some(db[&task_ids[3]].time_created);
+        assert_eq!(
+            ids(db.critical_path().unwrap()),
+            [2, 1, 0].map(|i| task_ids[i].clone())
+        );
+    }
+
+    #[test]
+    fn dependents_and_effective_priority() {
+        let (mut db, task_ids) = create_graph();
+        assert_eq!(db.get_transitive_dependents(&task_ids[3]).len(), 4);
+        assert_eq!(db.get_transitive_dependents(&task_ids[0]).len(), 0);
+
+        db[&task_ids[0]].priority = Some(Priority::HIGHEST);
+        db[&task_ids[2]].priority = Some(Priority::LOWEST);
+        assert_eq!(db.effective_priority(&task_ids[3]), Some(Priority::HIGHEST));
+        assert_eq!(db.effective_priority(&task_ids[4]), None);
+
+        // completed dependents don't wait on their dependencies anymore
+        db[&task_ids[1]].time_completed = Some(db[&task_ids[1]].time_created);
+        assert_eq!(db.get_transitive_dependents(&task_ids[3]).len(), 2);
+        assert_eq!(db.effective_priority(&task_ids[3]), Some(Priority::LOWEST));
+
+        let mut tasks = db.get_all_tasks().collect::<Vec<_>>();
+        db.sort_tasks(&mut tasks, TaskOrder::UnblockedWork);
+        assert_eq!(tasks[0].id(), &task_ids[3]);
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut db = Database::default();
+        let task_ids = add_tasks(&mut db, 2);
+        // bypass cycle detection, like in an old database
+        let indices = task_ids
+            .iter()
+            .map(|id| db.get_node_index(id).unwrap())
+            .collect::<Vec<_>>();
+        db.graph.add_edge(indices[0], indices[1], TaskDependency);
+        db.graph.add_edge(indices[1], indices[0], TaskDependency);
+
+        let error = db.topological_order().unwrap_err();
+        assert_eq!(error.0.len(), 2);
+        assert!(db.critical_path().is_err());
+    }
+}
@@ -1,31 +1,33 @@
-use std::ops::{Index, IndexMut};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    ops::{Index, IndexMut},
+};
 
 use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
 use time::OffsetDateTime;
 
 use super::*;
+use crate::errors::{DependencyCycleError, TaskError};
 
 impl Index<&TaskId> for Database {
     type Output = Task;
 
     fn index(&self, task_id: &TaskId) -> &Self::Output {
-        let node_index = self.get_node_index(task_id);
-        let Some(node_index) = node_index else {
+        let Some(task) = self.get(task_id) else {
             panic!("Index not found");
         };
 
-        &self.graph[node_index]
+        task
     }
 }
 
 impl IndexMut<&TaskId> for Database {
     fn index_mut(&mut self, task_id: &TaskId) -> &mut Self::Output {
-        let node_index = self.get_node_index(task_id);
-        let Some(node_index) = node_index else {
+        let Some(task) = self.get_mut(task_id) else {
             panic!("Index not found");
         };
 
-        &mut self.graph[node_index]
+        task
     }
 }
 
@@ -44,48 +46,428 @@ impl Database {
         self.graph.remove_node(task_index);
     }
 
+    /// Gets the task with the given id, or `None` if it does not exist.
+    #[must_use]
+    pub fn get(&self, task_id: &TaskId) -> Option<&Task> {
+        let node_index = self.get_node_index(task_id)?;
+        Some(&self.graph[node_index])
+    }
+
+    /// Gets a mutable reference to the task with the given id, or `None` if it does not exist.
+    #[must_use]
+    pub fn get_mut(&mut self, task_id: &TaskId) -> Option<&mut Task> {
+        let node_index = self.get_node_index(task_id)?;
+        Some(&mut self.graph[node_index])
+    }
+
+    /// Returns whether a task with the given id exists in the database.
+    #[must_use]
+    pub fn contains_task(&self, task_id: &TaskId) -> bool {
+        self.get_node_index(task_id).is_some()
+    }
+
     /// Get all tasks in the database.
     pub fn get_all_tasks(&self) -> impl Iterator<Item = &Task> + '_ {
         self.graph.node_weights()
     }
 
+    /// Adds a tag to the given task. Returns whether the tag was added, which is not the case if
+    /// the task already had this tag or the tag is empty.
+    pub fn add_tag(&mut self, task_id: &TaskId, tag: String) -> Result<bool, TaskError> {
+        let task = self
+            .get_mut(task_id)
+            .ok_or_else(|| TaskError::NotFound(task_id.clone()))?;
+
+        Ok(task.add_tag(tag))
+    }
+
+    /// Removes a tag from the given task. Returns whether the task had this tag.
+    pub fn remove_tag(&mut self, task_id: &TaskId, tag: &str) -> Result<bool, TaskError> {
+        let task = self
+            .get_mut(task_id)
+            .ok_or_else(|| TaskError::NotFound(task_id.clone()))?;
+
+        Ok(task.remove_tag(tag))
+    }
+
+    /// Renames a tag on every task in the database. If a task already has a tag with the new name,
+    /// the old tag is removed instead. Returns the amount of tasks that were changed.
+    pub fn rename_tag(&mut self, old_name: &str, new_name: &str) -> usize {
+        let new_name = new_name.trim();
+        if old_name == new_name || new_name.is_empty() {
+            return 0;
+        }
+
+        let mut changed_count = 0;
+        for task in self.graph.node_weights_mut() {
+            let Some(index) = task.tags.iter().position(|t| t == old_name) else {
+                continue;
+            };
+
+            if task.tags.iter().any(|t| t == new_name) {
+                task.tags.remove(index);
+            } else {
+                task.tags[index] = new_name.to_string();
+            }
+            changed_count += 1;
+        }
+
+        changed_count
+    }
+
+    /// Gets every distinct tag in the database, along with the amount of tasks that have it.
+    #[must_use]
+    pub fn get_tag_counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.graph.node_weights().flat_map(|t| &t.tags) {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
     /// Add a task dependency between 2 tasks. This indicates that one task depends on another.
-    pub fn add_dependency(&mut self, from: &TaskId, to: &TaskId) {
+    ///
+    /// If the new dependency would introduce a cycle, no changes are made and the tasks on that
+    /// cycle are returned in the error. See [`Self::find_dependency_cycle`]. Adding a dependency
+    /// that already exists does nothing.
+    ///
+    /// Panics if either task id can not be resolved. See [`Self::try_add_dependency`] for a
+    /// non-panicking alternative.
+    pub fn add_dependency(
+        &mut self,
+        from: &TaskId,
+        to: &TaskId,
+    ) -> Result<(), DependencyCycleError> {
         let from_index = self
             .get_node_index(from)
             .expect("should be able to resolve task id");
         let to_index = self
             .get_node_index(to)
             .expect("should be able to resolve task id");
+        if self.graph.contains_edge(from_index, to_index) {
+            return Ok(());
+        }
 
+        if let Some(cycle) = self.find_dependency_cycle(from, to) {
+            return Err(DependencyCycleError(cycle));
+        }
+
+        self.graph.add_edge(from_index, to_index, TaskDependency);
+        Ok(())
+    }
+
+    /// Adds a dependency between 2 tasks without checking for cycles. This is only meant for
+    /// restoring a previous state of the database, which may already contain cycles, such as when
+    /// undoing a change. Use [`Self::add_dependency`] for new dependencies. Returns whether the
+    /// dependency was added, which is not the case if it already exists.
+    ///
+    /// Panics if either task id can not be resolved.
+    pub fn restore_dependency(&mut self, from: &TaskId, to: &TaskId) -> bool {
+        let from_index = self
+            .get_node_index(from)
+            .expect("should be able to resolve task id");
+        let to_index = self
+            .get_node_index(to)
+            .expect("should be able to resolve task id");
+        if self.graph.contains_edge(from_index, to_index) {
+            return false;
+        }
         self.graph.add_edge(from_index, to_index, TaskDependency);
+        true
+    }
+
+    /// Add a task dependency between 2 tasks, like [`Self::add_dependency`]. If either task id can
+    /// not be resolved, an error is returned instead of panicking.
+    pub fn try_add_dependency(&mut self, from: &TaskId, to: &TaskId) -> Result<(), TaskError> {
+        self.ensure_task_exists(from)?;
+        self.ensure_task_exists(to)?;
+
+        Ok(self.add_dependency(from, to)?)
+    }
+
+    /// Removes the dependency of one task on another. Returns whether a dependency was removed. If
+    /// either task id was not found or there is no such dependency, no changes are made.
+    pub fn remove_dependency(&mut self, from: &TaskId, to: &TaskId) -> bool {
+        let (Some(from_index), Some(to_index)) =
+            (self.get_node_index(from), self.get_node_index(to))
+        else {
+            return false;
+        };
+        let Some(edge_index) = self.graph.find_edge(from_index, to_index) else {
+            return false;
+        };
+
+        self.graph.remove_edge(edge_index);
+        true
+    }
+
+    /// Checks whether adding a dependency from `from` to `to` would introduce a cycle.
+    ///
+    /// If it would, the tasks on that cycle are returned in dependency order, starting with `from`
+    /// and followed by `to`. The last task in the list depends on `from`.
+    #[must_use]
+    pub fn find_dependency_cycle(&self, from: &TaskId, to: &TaskId) -> Option<Vec<TaskId>> {
+        let from_index = self
+            .get_node_index(from)
+            .expect("should be able to resolve task id");
+        let to_index = self
+            .get_node_index(to)
+            .expect("should be able to resolve task id");
+
+        // breadth-first search from the new dependency back to the dependent task, keeping track
+        // of how we reached each task so the path can be reconstructed.
+        let mut reached_from = HashMap::from([(to_index, to_index)]);
+        let mut queue = VecDeque::from([to_index]);
+        while let Some(current) = queue.pop_front() {
+            if current == from_index {
+                let mut cycle = vec![];
+                let mut node = current;
+                while node != to_index {
+                    node = reached_from[&node];
+                    cycle.push(self.graph[node].id.clone());
+                }
+                cycle.push(from.clone());
+                cycle.reverse();
+                return Some(cycle);
+            }
+
+            for next in self.graph.neighbors_directed(current, Direction::Outgoing) {
+                reached_from.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    current
+                });
+            }
+        }
+
+        None
     }
 
     /// Gets all the tasks the given task depends on.
+    ///
+    /// Panics if the task id can not be resolved. See [`Self::try_get_dependencies`] for a
+    /// non-panicking alternative.
     pub fn get_dependencies(&self, source: &TaskId) -> impl Iterator<Item = &Task> + '_ {
+        self.try_get_dependencies(source)
+            .expect("should be able to resolve task id")
+    }
+
+    /// Gets all the tasks the given task depends on, or an error if the task does not exist.
+    pub fn try_get_dependencies(
+        &self,
+        source: &TaskId,
+    ) -> Result<impl Iterator<Item = &Task> + '_, TaskError> {
         let source_index = self
             .get_node_index(source)
-            .expect("should be able to resolve task id");
+            .ok_or_else(|| TaskError::NotFound(source.clone()))?;
 
-        self.graph
+        Ok(self
+            .graph
             .edges_directed(source_index, Direction::Outgoing)
             .map(|edge| edge.target())
-            .map(|target| &self.graph[target])
+            .map(|target| &self.graph[target]))
+    }
+
+    /// Returns whether any of the tasks the given task depends on are not completed yet.
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn has_unfinished_dependencies(&self, task_id: &TaskId) -> bool {
+        self.get_dependencies(task_id)
+            .any(|dep| dep.time_completed.is_none())
+    }
+
+    /// Gets the unfinished tasks that the given task (indirectly) depends on, but that are due
+    /// after it. These tasks should be finished first, so the due date of the given task can
+    /// likely not be met.
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn get_due_date_conflicts(&self, task_id: &TaskId) -> Vec<&Task> {
+        let Some(due_date) = self[task_id].due_date else {
+            return vec![];
+        };
+
+        let start_index = self
+            .get_node_index(task_id)
+            .expect("should be able to resolve task id");
+        let mut visited = HashSet::from([start_index]);
+        let mut stack = vec![start_index];
+        let mut conflicts = vec![];
+        while let Some(index) = stack.pop() {
+            for dependency in self.graph.neighbors_directed(index, Direction::Outgoing) {
+                let task = &self.graph[dependency];
+                if task.time_completed.is_some() || !visited.insert(dependency) {
+                    continue;
+                }
+                if task.due_date.is_some_and(|d| d > due_date) {
+                    conflicts.push(task);
+                }
+                stack.push(dependency);
+            }
+        }
+        conflicts
+    }
+
+    /// Marks the given task as completed at the given time, and stops its timer if it is running.
+    /// If the task is already completed, no changes are made.
+    ///
+    /// If the task has a [`Task::recurrence`], the next instance is created with the same title,
+    /// description, priority, tags and dependencies, and the recurrence moves to the new instance.
+    /// Tasks that depend on the completed instance are not changed, so they don't get blocked
+    /// again. Returns the id of the new instance, if one was created.
+    pub fn complete_task(
+        &mut self,
+        task_id: &TaskId,
+        time: OffsetDateTime,
+    ) -> Result<Option<TaskId>, TaskError> {
+        self.complete_task_with_next_id(task_id, time, TaskId::new())
+    }
+
+    /// Like [`Self::complete_task`], but the next instance gets the given id.
+    pub(super) fn complete_task_with_next_id(
+        &mut self,
+        task_id: &TaskId,
+        time: OffsetDateTime,
+        next_id: TaskId,
+    ) -> Result<Option<TaskId>, TaskError> {
+        let task_index = self
+            .get_node_index(task_id)
+            .ok_or_else(|| TaskError::NotFound(task_id.clone()))?;
+        let task = &mut self.graph[task_index];
+        if task.time_completed.is_some() {
+            return Ok(None);
+        }
+
+        task.time_completed = Some(time);
+        task.stop_timer(time);
+        let Some(recurrence) = task.recurrence.take() else {
+            return Ok(None);
+        };
+
+        let next_task = Task {
+            id: next_id,
+            title: task.title.clone(),
+            description: task.description.clone(),
+            time_created: time,
+            sessions: vec![],
+            time_completed: None,
+            due_date: Some(recurrence.next_due_date(task.due_date, time.date())),
+            recurrence: Some(recurrence),
+            priority: task.priority,
+            estimate: task.estimate,
+            tags: task.tags.clone(),
+        };
+        let next_id = next_task.id.clone();
+        let next_index = self.graph.add_node(next_task);
+        self.task_id_to_index.insert(next_id.clone(), next_index);
+
+        // nothing depends on the new task yet, so these edges can not introduce a cycle
+        let dependencies = self
+            .graph
+            .neighbors_directed(task_index, Direction::Outgoing)
+            .collect::<Vec<_>>();
+        for dependency in dependencies {
+            self.graph.add_edge(next_index, dependency, TaskDependency);
+        }
+
+        Ok(Some(next_id))
+    }
+
+    /// Marks a completed task as not completed, reverting [`Self::complete_task`]. Returns whether
+    /// the task was reopened, which is not the case if it was not completed.
+    ///
+    /// If completing the task created the next instance of a recurring task, that instance is
+    /// removed and the recurrence moves back to this task. If the next instance was changed since,
+    /// removing it would lose those changes, so no changes are made and an error is returned.
+    pub fn reopen_task(&mut self, task_id: &TaskId) -> Result<bool, TaskError> {
+        let task = self
+            .get(task_id)
+            .ok_or_else(|| TaskError::NotFound(task_id.clone()))?;
+        let Some(completed) = task.time_completed else {
+            return Ok(false);
+        };
+
+        // the next instance is created at the exact time the task was completed
+        let next = self
+            .get_all_tasks()
+            .find(|next| next.id != task.id && next.time_created == completed);
+        let recurrence = match next {
+            Some(next) if self.is_unchanged_next_instance(task, next) => {
+                let next_id = next.id.clone();
+                let recurrence = next.recurrence;
+                self.remove_task(&next_id);
+                recurrence
+            }
+            Some(next) => return Err(TaskError::NextInstanceChanged(next.id.clone())),
+            None => None,
+        };
+
+        let task = &mut self[task_id];
+        task.time_completed = None;
+        if recurrence.is_some() {
+            task.recurrence = recurrence;
+        }
+        Ok(true)
+    }
+
+    /// Checks whether `next` is still exactly the instance that completing `task` created.
+    fn is_unchanged_next_instance(&self, task: &Task, next: &Task) -> bool {
+        let Some(recurrence) = next.recurrence else {
+            return false;
+        };
+        let completed = task.time_completed.unwrap_or(next.time_created);
+        let dependency_ids = |id: &TaskId| {
+            self.get_dependencies(id)
+                .map(Task::id)
+                .collect::<HashSet<_>>()
+        };
+
+        next.time_completed.is_none()
+            && next.sessions.is_empty()
+            && next.title == task.title
+            && next.description == task.description
+            && next.priority == task.priority
+            && next.estimate == task.estimate
+            && next.tags == task.tags
+            && next.due_date == Some(recurrence.next_due_date(task.due_date, completed.date()))
+            && dependency_ids(&next.id) == dependency_ids(&task.id)
+            && self.get_inverse_dependencies(&next.id).next().is_none()
     }
 
     /// Gets all the tasks that depend on the given task.
+    ///
+    /// Panics if the task id can not be resolved. See [`Self::try_get_inverse_dependencies`] for a
+    /// non-panicking alternative.
     pub fn get_inverse_dependencies(&self, target: &TaskId) -> impl Iterator<Item = &Task> + '_ {
+        self.try_get_inverse_dependencies(target)
+            .expect("should be able to resolve task id")
+    }
+
+    /// Gets all the tasks that depend on the given task, or an error if the task does not exist.
+    pub fn try_get_inverse_dependencies(
+        &self,
+        target: &TaskId,
+    ) -> Result<impl Iterator<Item = &Task> + '_, TaskError> {
         let target_index = self
             .get_node_index(target)
-            .expect("should be able to resolve task id");
+            .ok_or_else(|| TaskError::NotFound(target.clone()))?;
 
-        self.graph
+        Ok(self
+            .graph
             .edges_directed(target_index, Direction::Incoming)
             .map(|edge| edge.source())
-            .map(|source| &self.graph[source])
+            .map(|source| &self.graph[source]))
     }
 
-    fn get_node_index(&self, task_id: &TaskId) -> Option<NodeIndex> {
+    pub(super) fn ensure_task_exists(&self, task_id: &TaskId) -> Result<(), TaskError> {
+        if self.contains_task(task_id) {
+            Ok(())
+        } else {
+            Err(TaskError::NotFound(task_id.clone()))
+        }
+    }
+
+    pub(super) fn get_node_index(&self, task_id: &TaskId) -> Option<NodeIndex> {
         self.task_id_to_index.get(task_id).copied().or_else(|| {
             // this fallback check exists in case we add a new node and it isn't in the cache.
             // this check should be removed when insertion of new tasks is managed here.
@@ -109,9 +491,14 @@ impl Task {
         Self {
             id: TaskId::new(),
             title,
+            description: String::new(),
             time_created,
-            time_started: None,
+            sessions: vec![],
             time_completed: None,
+            due_date: None,
+            recurrence: None,
+            priority: None,
+            estimate: None,
             tags: vec![],
         }
     }
@@ -121,4 +508,303 @@ impl Task {
     pub fn id(&self) -> &TaskId {
         &self.id
     }
+
+    /// Adds a tag to this task. Returns whether the tag was added, which is not the case if this
+    /// task already has this tag or the tag is empty.
+    pub fn add_tag(&mut self, tag: String) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.tags.iter().any(|t| t == tag) {
+            return false;
+        }
+
+        self.tags.push(tag.to_string());
+        true
+    }
+
+    /// Removes a tag from this task. Returns whether this task had the tag.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        let len_before = self.tags.len();
+        self.tags.retain(|t| t != tag);
+        self.tags.len() != len_before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_utils::add_tasks;
+
+    #[test]
+    fn add_dependency() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 2);
+
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+
+        let dependencies = db.get_dependencies(&ids[0]).collect::<Vec<_>>();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].id(), &ids[1]);
+    }
+
+    #[test]
+    fn add_dependency_ignores_duplicate() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 2);
+
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+
+        assert_eq!(db.get_dependencies(&ids[0]).count(), 1);
+        assert_eq!(db.get_inverse_dependencies(&ids[1]).count(), 1);
+    }
+
+    #[test]
+    fn add_dependency_rejects_self_dependency() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 1);
+
+        let error = db.add_dependency(&ids[0], &ids[0]).unwrap_err();
+        assert_eq!(error.0, vec![ids[0].clone()]);
+        assert_eq!(db.get_dependencies(&ids[0]).count(), 0);
+    }
+
+    #[test]
+    fn add_dependency_rejects_cycle() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 4);
+
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[1], &ids[2]).unwrap();
+        db.add_dependency(&ids[2], &ids[3]).unwrap();
+
+        let error = db.add_dependency(&ids[3], &ids[0]).unwrap_err();
+        assert_eq!(
+            error.0,
+            vec![
+                ids[3].clone(),
+                ids[0].clone(),
+                ids[1].clone(),
+                ids[2].clone()
+            ]
+        );
+        assert_eq!(db.get_dependencies(&ids[3]).count(), 0);
+    }
+
+    #[test]
+    fn get_unknown_task() {
+        let mut db = Database::default();
+        add_tasks(&mut db, 1);
+        let unknown_id: TaskId = "unknown".parse().unwrap();
+
+        assert!(db.get(&unknown_id).is_none());
+        assert!(db.get_mut(&unknown_id).is_none());
+        assert!(!db.contains_task(&unknown_id));
+        assert!(matches!(
+            db.try_get_dependencies(&unknown_id),
+            Err(TaskError::NotFound(id)) if id == unknown_id
+        ));
+        assert!(matches!(
+            db.try_get_inverse_dependencies(&unknown_id),
+            Err(TaskError::NotFound(id)) if id == unknown_id
+        ));
+    }
+
+    #[test]
+    fn get_removed_task() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 2);
+
+        db.remove_task(&ids[0]);
+
+        assert!(db.get(&ids[0]).is_none());
+        assert_eq!(db.get(&ids[1]).map(|t| t.id()), Some(&ids[1]));
+    }
+
+    #[test]
+    fn try_add_dependency_unknown_task() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 1);
+        let unknown_id: TaskId = "unknown".parse().unwrap();
+
+        assert!(matches!(
+            db.try_add_dependency(&ids[0], &unknown_id),
+            Err(TaskError::NotFound(id)) if id == unknown_id
+        ));
+        assert!(matches!(
+            db.try_add_dependency(&unknown_id, &ids[0]),
+            Err(TaskError::NotFound(id)) if id == unknown_id
+        ));
+        assert!(matches!(
+            db.try_add_dependency(&ids[0], &ids[0]),
+            Err(TaskError::DependencyCycle(_))
+        ));
+    }
+
+    #[test]
+    fn remove_dependency() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 3);
+
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[0], &ids[2]).unwrap();
+
+        assert!(db.remove_dependency(&ids[0], &ids[1]));
+        assert!(!db.remove_dependency(&ids[0], &ids[1]));
+        assert!(!db.remove_dependency(&ids[1], &ids[0]));
+
+        let dependencies = db.get_dependencies(&ids[0]).collect::<Vec<_>>();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].id(), &ids[2]);
+        assert_eq!(db.get_inverse_dependencies(&ids[1]).count(), 0);
+    }
+
+    #[test]
+    fn add_tag_deduplicates() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 1);
+
+        assert!(db.add_tag(&ids[0], "tag".into()).unwrap());
+        assert!(!db.add_tag(&ids[0], "tag".into()).unwrap());
+        assert!(!db.add_tag(&ids[0], " tag ".into()).unwrap());
+        assert!(!db.add_tag(&ids[0], "".into()).unwrap());
+        assert_eq!(db[&ids[0]].tags, vec!["tag".to_string()]);
+    }
+
+    #[test]
+    fn remove_tag() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 1);
+        db.add_tag(&ids[0], "a".into()).unwrap();
+        db.add_tag(&ids[0], "b".into()).unwrap();
+
+        assert!(db.remove_tag(&ids[0], "a").unwrap());
+        assert!(!db.remove_tag(&ids[0], "a").unwrap());
+        assert_eq!(db[&ids[0]].tags, vec!["b".to_string()]);
+
+        let unknown_id: TaskId = "unknown".parse().unwrap();
+        assert!(db.remove_tag(&unknown_id, "b").is_err());
+    }
+
+    #[test]
+    fn rename_tag() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 3);
+        db.add_tag(&ids[0], "old".into()).unwrap();
+        db.add_tag(&ids[0], "other".into()).unwrap();
+        db.add_tag(&ids[1], "old".into()).unwrap();
+        db.add_tag(&ids[1], "new".into()).unwrap();
+        db.add_tag(&ids[2], "other".into()).unwrap();
+
+        assert_eq!(db.rename_tag("old", "new"), 2);
+
+        assert_eq!(
+            db[&ids[0]].tags,
+            vec!["new".to_string(), "other".to_string()]
+        );
+        assert_eq!(db[&ids[1]].tags, vec!["new".to_string()]);
+        assert_eq!(db[&ids[2]].tags, vec!["other".to_string()]);
+        assert_eq!(
+            db.get_tag_counts(),
+            BTreeMap::from([("new", 2), ("other", 2)])
+        );
+    }
+
+    #[test]
+    fn add_dependency_allows_diamond() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 4);
+
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[0], &ids[2]).unwrap();
+        db.add_dependency(&ids[1], &ids[3]).unwrap();
+        db.add_dependency(&ids[2], &ids[3]).unwrap();
+
+        assert_eq!(db.find_dependency_cycle(&ids[0], &ids[3]), None);
+    }
+
+    #[test]
+    fn due_date_conflicts() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 4);
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[1], &ids[2]).unwrap();
+        db.add_dependency(&ids[1], &ids[3]).unwrap();
+
+        let today = OffsetDateTime::now_utc().date();
+        db[&ids[0]].due_date = Some(today);
+        db[&ids[2]].due_date = today.next_day();
+        db[&ids[3]].due_date = today.previous_day();
+        assert!(db.get_due_date_conflicts(&ids[1]).is_empty());
+
+        let conflicts = db.get_due_date_conflicts(&ids[0]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id(), &ids[2]);
+
+        db[&ids[2]].time_completed = Some(OffsetDateTime::now_utc());
+        assert!(db.get_due_date_conflicts(&ids[0]).is_empty());
+    }
+
+    #[test]
+    fn complete_recurring_task() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 3);
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[2], &ids[0]).unwrap();
+        db.add_tag(&ids[0], "chore".into()).unwrap();
+        db[&ids[0]].recurrence = Some("weekly".parse().unwrap());
+
+        let time = OffsetDateTime::now_utc();
+        let next_id = db.complete_task(&ids[0], time).unwrap().unwrap();
+        assert_eq!(db[&ids[0]].time_completed, Some(time));
+        assert!(db[&ids[0]].recurrence.is_none());
+
+        let next = &db[&next_id];
+        assert_eq!(next.title, "task 0");
+        assert_eq!(next.tags, ["chore"]);
+        assert!(next.time_completed.is_none());
+        assert_eq!(
+            next.due_date,
+            time.date().checked_add(time::Duration::weeks(1))
+        );
+        assert!(next.recurrence.is_some());
+        let dependencies = db
+            .get_dependencies(&next_id)
+            .map(Task::id)
+            .collect::<Vec<_>>();
+        assert_eq!(dependencies, [&ids[1]]);
+        assert_eq!(db.get_inverse_dependencies(&next_id).count(), 0);
+
+        // completing again does nothing
+        assert_eq!(db.complete_task(&ids[0], time).unwrap(), None);
+        assert_eq!(db.get_all_tasks().count(), 4);
+    }
+
+    #[test]
+    fn reopen_recurring_task() {
+        let mut db = Database::default();
+        let ids = add_tasks(&mut db, 3);
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db.add_dependency(&ids[0], &ids[2]).unwrap();
+        db[&ids[0]].recurrence = Some("daily".parse().unwrap());
+        let recurrence = db[&ids[0]].recurrence;
+        let time = OffsetDateTime::now_utc();
+
+        let next_id = db.complete_task(&ids[0], time).unwrap().unwrap();
+        assert_eq!(db.reopen_task(&ids[0]), Ok(true));
+        assert_eq!(db.reopen_task(&ids[0]), Ok(false));
+        assert!(db[&ids[0]].time_completed.is_none());
+        assert_eq!(db[&ids[0]].recurrence, recurrence);
+        assert!(!db.contains_task(&next_id));
+        assert_eq!(db.get_all_tasks().count(), 3);
+
+        // a changed next instance is kept, and the task can not be reopened
+        let next_id = db.complete_task(&ids[0], time).unwrap().unwrap();
+        db[&next_id].title = "renamed".into();
+        assert_eq!(
+            db.reopen_task(&ids[0]),
+            Err(TaskError::NextInstanceChanged(next_id.clone()))
+        );
+        assert!(db[&ids[0]].time_completed.is_some());
+        assert!(db.contains_task(&next_id));
+    }
 }
@@ -1,10 +1,16 @@
 //! Contains a version-agnostic wrapper structure around the version-specific database structure.
 
-use std::path::Path;
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
-use super::{Database, DatabaseImpl};
+use super::{migrations, Database, DatabaseImpl, CURRENT_DATABASE_VERSION};
 use crate::errors::DatabaseReadError;
 
 /// A version-agnostic container for a database structure.
@@ -15,6 +21,14 @@ pub struct DatabaseFile {
     data: serde_json::Value,
 }
 
+/// Options for writing a [`DatabaseFile`] to disk.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// How many previous versions of the file to keep as timestamped backups next to it, such as
+    /// `.td.json.20240101T120000.000000000Z.bak`. When this is 0, no backups are created.
+    pub backup_count: usize,
+}
+
 impl DatabaseFile {
     /// Read the database file from disk in json format.
     pub fn read(path: &Path) -> Result<Self, DatabaseReadError> {
@@ -23,12 +37,159 @@ impl DatabaseFile {
         Ok(serde_json::from_slice(&file)?)
     }
 
-    /// Write the database file to disk in json format.
+    /// Write the database file to disk in json format, using the default [`WriteOptions`].
     pub fn write(&self, path: &Path) -> Result<(), DatabaseReadError> {
+        self.write_with_options(path, &WriteOptions::default())
+    }
+
+    /// Write the database file to disk in json format.
+    ///
+    /// The file is first written to a temporary file in the same directory, which then replaces
+    /// the original file. This ensures that the original file is never left half-written, even if
+    /// the process crashes or the disk is full. If the path is a symlink, the file it points to is
+    /// replaced instead, and the permissions of the original file are kept.
+    pub fn write_with_options(
+        &self,
+        path: &Path,
+        options: &WriteOptions,
+    ) -> Result<(), DatabaseReadError> {
         let json = serde_json::to_vec_pretty(self)?;
-        std::fs::write(path, json)?;
+        let path = &Self::resolve_symlinks(path);
+
+        let temp_path = Self::sibling_path(path, &format!(".{}.tmp", std::process::id()));
+        let write_result = (|| {
+            let mut temp_file = File::create(&temp_path)?;
+            if let Ok(metadata) = std::fs::metadata(path) {
+                temp_file.set_permissions(metadata.permissions())?;
+            }
+            temp_file.write_all(&json)?;
+            temp_file.sync_all()?;
+
+            if options.backup_count > 0 && path.exists() {
+                let backup_path = Self::sibling_path(path, &format!(".{}.bak", Self::timestamp()));
+                std::fs::copy(path, backup_path)?;
+            }
+
+            std::fs::rename(&temp_path, path)
+        })();
+        if let Err(e) = write_result {
+            _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        Self::sync_parent_dir(path)?;
+
+        if options.backup_count > 0 {
+            Self::remove_old_backups(path, options.backup_count)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the paths of all timestamped backups of the database file at the given path, oldest
+    /// first. See [`WriteOptions::backup_count`].
+    pub fn list_backups(path: &Path) -> Result<Vec<PathBuf>, DatabaseReadError> {
+        let path = &Self::resolve_symlinks(path);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let prefix = format!("{file_name}.");
+
+        let mut backups = vec![];
+        for entry in std::fs::read_dir(Self::parent_dir(path))? {
+            let entry_path = entry?.path();
+            let entry_name = entry_path.file_name().unwrap_or_default().to_string_lossy();
+            let is_backup = entry_name
+                .strip_prefix(&prefix)
+                .and_then(|x| x.strip_suffix(".bak"))
+                .is_some_and(Self::is_timestamp);
+            if is_backup {
+                backups.push(entry_path);
+            }
+        }
+
+        // timestamps sort chronologically
+        backups.sort();
+        Ok(backups)
+    }
+
+    fn remove_old_backups(path: &Path, backup_count: usize) -> Result<(), DatabaseReadError> {
+        let backups = Self::list_backups(path)?;
+        let remove_count = backups.len().saturating_sub(backup_count);
+        for backup in &backups[..remove_count] {
+            std::fs::remove_file(backup)?;
+        }
         Ok(())
     }
+
+    fn parent_dir(path: &Path) -> &Path {
+        match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        }
+    }
+
+    /// Gets the path of the file that `path` points to, or `path` itself if it does not exist yet.
+    fn resolve_symlinks(path: &Path) -> PathBuf {
+        std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+
+    fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+        let mut file_name: OsString = path.file_name().unwrap_or_default().to_owned();
+        file_name.push(suffix);
+        path.with_file_name(file_name)
+    }
+
+    #[cfg(unix)]
+    fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+        // the rename is only durable once the directory entry itself is written to disk
+        File::open(Self::parent_dir(path))?.sync_all()
+    }
+
+    #[cfg(not(unix))]
+    fn sync_parent_dir(_path: &Path) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// A UTC timestamp that sorts chronologically, eg. `20240101T120000.000000000Z`.
+    fn timestamp() -> String {
+        let now = OffsetDateTime::now_utc();
+        format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}.{:09}Z",
+            now.year(),
+            now.month() as u8,
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second(),
+            now.nanosecond()
+        )
+    }
+
+    fn is_timestamp(text: &str) -> bool {
+        let bytes = text.as_bytes();
+        bytes.len() == "20240101T120000.000000000Z".len()
+            && bytes.iter().enumerate().all(|(i, b)| match i {
+                8 => *b == b'T',
+                15 => *b == b'.',
+                25 => *b == b'Z',
+                _ => b.is_ascii_digit(),
+            })
+    }
+
+    /// Returns whether this file uses an older database version, meaning that it will be upgraded
+    /// to [`CURRENT_DATABASE_VERSION`] when it is loaded. Writing the loaded database back to disk
+    /// will make it unreadable for older versions of td.
+    #[must_use]
+    pub fn requires_upgrade(&self) -> bool {
+        self.version < CURRENT_DATABASE_VERSION
+    }
+
+    /// Copies the database file at the given path to a backup file next to it, so it is kept
+    /// around when the database is upgraded. The backup file name includes the given version,
+    /// e.g. `.td.json.v1.bak`. Returns the path of the backup.
+    pub fn create_backup(path: &Path, version: u8) -> Result<PathBuf, DatabaseReadError> {
+        let backup_path = Self::sibling_path(path, &format!(".v{version}.bak"));
+        std::fs::copy(path, &backup_path)?;
+        Ok(backup_path)
+    }
 }
 
 impl Default for DatabaseFile {
@@ -44,12 +205,9 @@ impl Default for DatabaseFile {
 impl TryInto<Database> for DatabaseFile {
     type Error = DatabaseReadError;
 
-    // NOTE: migrations would happen here
     fn try_into(self) -> Result<Database, Self::Error> {
-        if self.version != 1 {
-            return Err(DatabaseReadError::UnknownVersion(self.version));
-        }
-        Ok(serde_json::from_value(self.data)?)
+        let data = migrations::migrate(self.version, self.data)?;
+        Ok(serde_json::from_value(data)?)
     }
 }
 
@@ -61,3 +219,254 @@ impl From<&Database> for DatabaseFile {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_v1_database() {
+        let file: DatabaseFile = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "data": {
+                "tasks": [{
+                    "id": "done",
+                    "title": "done",
+                    "time_created": "2023-01-07 22:36:27.9433541 +01:00:00",
+                    "time_completed": "2023-01-27 14:11:31.4254526 +01:00:00",
+                    "tags": ["P:1"],
+                }, {
+                    "dependencies": ["done"],
+                    "id": "blocked",
+                    "title": "blocked",
+                    "time_created": "2023-01-08 10:00:00.0 +01:00:00",
+                    "tags": ["P:1", "backend"],
+                }, {
+                    "id": "other",
+                    "title": "other",
+                    "time_created": "2023-01-09 10:00:00.0 +01:00:00",
+                    "tags": ["P:2"],
+                }],
+            },
+        }))
+        .expect("valid json");
+        assert_eq!(file.version, 1);
+        assert!(file.requires_upgrade());
+
+        let db: Database = file.try_into().expect("database should migrate");
+        assert_eq!(db.get_all_tasks().count(), 3);
+        let blocked = "blocked".parse().unwrap();
+        let dependencies = db.get_dependencies(&blocked).map(|t| t.title.as_str());
+        assert_eq!(dependencies.collect::<Vec<_>>(), ["done"]);
+
+        let file = DatabaseFile::from(&db);
+        assert!(!file.requires_upgrade());
+        let task = &file.data["tasks"][0];
+        assert_eq!(task["time_created"], "2023-01-07T22:36:27.9433541+01:00");
+
+        // priority tags are lifted into their own field
+        let priorities = db.get_all_tasks().filter_map(|t| t.priority);
+        assert_eq!(priorities.filter(|p| p.value() == 1).count(), 2);
+        assert_eq!(db[&blocked].tags, ["backend"]);
+        assert!(db
+            .get_all_tasks()
+            .flat_map(|t| &t.tags)
+            .all(|tag| !tag.starts_with("P:")));
+    }
+
+    #[test]
+    fn migrate_priority_tags() {
+        let file: DatabaseFile = serde_json::from_value(serde_json::json!({
+            "version": 2,
+            "data": {
+                "tasks": [{
+                    "id": "task",
+                    "title": "title",
+                    "time_created": "2024-01-01T12:00:00Z",
+                    "tags": ["P:3", "backend", "P:1", "P:9"],
+                }],
+            },
+        }))
+        .unwrap();
+
+        let db: Database = file.try_into().expect("database should migrate");
+        let task = db.get_all_tasks().next().unwrap();
+        assert_eq!(task.priority.map(|p| p.value()), Some(1));
+        assert_eq!(task.tags, ["backend", "P:9"]);
+    }
+
+    #[test]
+    fn migrate_start_times_to_sessions() {
+        let file: DatabaseFile = serde_json::from_value(serde_json::json!({
+            "version": 3,
+            "data": {
+                "tasks": [{
+                    "id": "started",
+                    "title": "started",
+                    "time_created": "2024-01-01T12:00:00Z",
+                    "time_started": "2024-01-02T12:00:00Z",
+                }, {
+                    "id": "completed",
+                    "title": "completed",
+                    "time_created": "2024-01-01T12:00:00Z",
+                    "time_started": "2024-01-02T12:00:00Z",
+                    "time_completed": "2024-01-02T14:00:00Z",
+                }],
+            },
+        }))
+        .unwrap();
+
+        let db: Database = file.try_into().expect("database should migrate");
+        let now = time::OffsetDateTime::now_utc();
+        let spent = |id: &str| {
+            let task = db.get(&id.parse().unwrap()).unwrap();
+            assert!(task.is_started());
+            assert!(!task.is_timer_running());
+            task.time_spent(now).whole_hours()
+        };
+        assert_eq!(spent("started"), 0);
+        assert_eq!(spent("completed"), 2);
+    }
+
+    #[test]
+    fn migrate_v3_fields() {
+        let v3_file = |task: serde_json::Value| -> DatabaseFile {
+            serde_json::from_value(serde_json::json!({
+                "version": 3,
+                "data": { "tasks": [task] },
+            }))
+            .unwrap()
+        };
+        let file = v3_file(serde_json::json!({
+            "id": "task",
+            "title": "task",
+            "time_created": "2024-01-01T12:00:00Z",
+            "recurrence": { "interval": 2, "unit": "weeks", "anchor": "schedule" },
+            "priority": 1,
+            "estimate": "1d 4h",
+        }));
+
+        let db: Database = file.try_into().expect("database should migrate");
+        let task = db.get(&"task".parse().unwrap()).unwrap();
+        assert_eq!(task.recurrence, Some("2w".parse().unwrap()));
+        assert_eq!(task.priority, Some(1.try_into().unwrap()));
+        assert_eq!(task.estimate, Some("1d 4h".parse().unwrap()));
+
+        for (field, invalid) in [
+            ("priority", serde_json::json!(9)),
+            ("estimate", "soon".into()),
+        ] {
+            let mut task = serde_json::json!({
+                "id": "task",
+                "title": "task",
+                "time_created": "2024-01-01T12:00:00Z",
+            });
+            task[field] = invalid;
+            assert!(TryInto::<Database>::try_into(v3_file(task)).is_err());
+        }
+    }
+
+    fn create_temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("td-test-{}", nanoid::nanoid!()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_replaces_file() {
+        let dir = create_temp_dir();
+        let path = dir.join("db.json");
+        std::fs::write(&path, "old contents").unwrap();
+
+        DatabaseFile::default().write(&path).unwrap();
+
+        let file = DatabaseFile::read(&path).unwrap();
+        assert_eq!(file.version, CURRENT_DATABASE_VERSION);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_keeps_backups() {
+        let dir = create_temp_dir();
+        let path = dir.join("db.json");
+        let options = WriteOptions { backup_count: 2 };
+
+        // the first write has nothing to back up yet
+        DatabaseFile::default()
+            .write_with_options(&path, &options)
+            .unwrap();
+        assert_eq!(DatabaseFile::list_backups(&path).unwrap().len(), 0);
+
+        for _ in 0..4 {
+            DatabaseFile::default()
+                .write_with_options(&path, &options)
+                .unwrap();
+        }
+        let backups = DatabaseFile::list_backups(&path).unwrap();
+        assert_eq!(backups.len(), 2);
+        DatabaseFile::read(&backups[0]).expect("backup should be a valid database");
+
+        // version backups are not touched
+        let version_backup = DatabaseFile::create_backup(&path, 1).unwrap();
+        DatabaseFile::default()
+            .write_with_options(&path, &options)
+            .unwrap();
+        assert!(version_backup.exists());
+        assert_eq!(DatabaseFile::list_backups(&path).unwrap().len(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn write_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = create_temp_dir();
+        let target = dir.join("db.json");
+        let link = dir.join("link.json");
+        DatabaseFile::default().write(&target).unwrap();
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o600)).unwrap();
+        symlink(&target, &link).unwrap();
+
+        let options = WriteOptions { backup_count: 1 };
+        for _ in 0..2 {
+            DatabaseFile::default()
+                .write_with_options(&link, &options)
+                .unwrap();
+        }
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(DatabaseFile::list_backups(&link).unwrap().len(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_write_removes_temp_file() {
+        let dir = create_temp_dir();
+        // a directory can not be replaced by a file
+        let path = dir.join("db.json");
+        std::fs::create_dir(&path).unwrap();
+
+        assert!(DatabaseFile::default().write(&path).is_err());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reject_unknown_versions() {
+        for version in [0, CURRENT_DATABASE_VERSION + 1] {
+            let file = DatabaseFile {
+                version,
+                data: serde_json::Value::Null,
+            };
+            assert!(matches!(
+                TryInto::<Database>::try_into(file),
+                Err(DatabaseReadError::UnknownVersion(v)) if v == version
+            ));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Estimates of how much work a task is, and how much work is left before it can be completed.
+
+use std::{fmt::Display, iter::Sum, ops::Add, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Database, TaskId};
+use crate::errors::EstimateParseError;
+
+/// An estimate of how long a task takes, with a precision of a minute.
+///
+/// Estimates are measured in working time, so a day is 8 hours and a week is 5 days. They can be
+/// parsed from text such as `30m`, `2h`, `3d`, `1w` or `1d 4h`, and are stored in that format.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct Estimate {
+    minutes: u32,
+}
+
+/// The total estimate of a task and all the unfinished tasks it (indirectly) depends on. See
+/// [`Database::remaining_estimate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RemainingEstimate {
+    /// The sum of all estimates.
+    pub total: Estimate,
+    /// The amount of tasks without an estimate, which are not included in the total.
+    pub unestimated_count: usize,
+}
+
+impl Estimate {
+    const UNITS: [(char, u32); 4] = [('w', 5 * 8 * 60), ('d', 8 * 60), ('h', 60), ('m', 1)];
+
+    /// Creates an estimate of the given amount of minutes.
+    #[must_use]
+    pub fn from_minutes(minutes: u32) -> Self {
+        Self { minutes }
+    }
+
+    /// Gets the length of this estimate in minutes.
+    #[must_use]
+    pub fn minutes(self) -> u32 {
+        self.minutes
+    }
+}
+
+impl Add for Estimate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::from_minutes(self.minutes.saturating_add(rhs.minutes))
+    }
+}
+
+impl Sum for Estimate {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.minutes == 0 {
+            return write!(f, "0m");
+        }
+
+        let mut remaining = self.minutes;
+        let mut parts = vec![];
+        for (unit, minutes) in Self::UNITS {
+            if remaining >= minutes {
+                parts.push(format!("{}{unit}", remaining / minutes));
+                remaining %= minutes;
+            }
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl FromStr for Estimate {
+    type Err = EstimateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || EstimateParseError(s.to_string());
+        let text = s.trim().to_lowercase();
+        if text.is_empty() {
+            return Err(error());
+        }
+
+        // a sequence of amounts with a unit, such as `1d 4h` or `1d4h`
+        let mut total = 0u32;
+        let mut amount = String::new();
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            if c.is_ascii_digit() {
+                amount.push(c);
+                continue;
+            }
+
+            let (_, unit_minutes) = Self::UNITS
+                .into_iter()
+                .find(|(unit, _)| *unit == c)
+                .ok_or_else(error)?;
+            let amount = std::mem::take(&mut amount)
+                .parse::<u32>()
+                .map_err(|_| error())?;
+            total = amount
+                .checked_mul(unit_minutes)
+                .and_then(|minutes| total.checked_add(minutes))
+                .ok_or_else(error)?;
+        }
+
+        // every amount needs a unit
+        if !amount.is_empty() {
+            return Err(error());
+        }
+
+        Ok(Self::from_minutes(total))
+    }
+}
+
+impl TryFrom<String> for Estimate {
+    type Error = EstimateParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Estimate> for String {
+    fn from(value: Estimate) -> Self {
+        value.to_string()
+    }
+}
+
+impl Database {
+    /// Gets the total estimate of the given task and all the unfinished tasks it (indirectly)
+    /// depends on. Every task is only counted once, even if multiple tasks depend on it. Completed
+    /// tasks are not counted.
+    ///
+    /// Panics if the task id can not be resolved.
+    #[must_use]
+    pub fn remaining_estimate(&self, task_id: &TaskId) -> RemainingEstimate {
+        let task = &self[task_id];
+        let tasks = self
+            .get_transitive_dependencies(task_id)
+            .into_iter()
+            .chain((task.time_completed.is_none()).then_some(task));
+
+        let mut remaining = RemainingEstimate::default();
+        for task in tasks {
+            match task.estimate {
+                Some(estimate) => remaining.total = remaining.total + estimate,
+                None => remaining.unestimated_count += 1,
+            }
+        }
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_utils::add_task;
+
+    #[test]
+    fn parse_estimates() {
+        let parse = |text: &str| text.parse::<Estimate>().map(Estimate::minutes);
+        assert_eq!(parse("30m"), Ok(30));
+        assert_eq!(parse("2H"), Ok(120));
+        assert_eq!(parse("1d 4h"), Ok(12 * 60));
+        assert_eq!(parse("1w1d"), Ok(6 * 8 * 60));
+
+        for text in ["30m", "2h", "1w 2d 3h 4m"] {
+            assert_eq!(text.parse::<Estimate>().unwrap().to_string(), text);
+        }
+        assert_eq!(Estimate::from_minutes(90).to_string(), "1h 30m");
+
+        for invalid in ["", "2", "h", "2y", "1.5h", "99999999w"] {
+            assert_eq!(
+                invalid.parse::<Estimate>(),
+                Err(EstimateParseError(invalid.into()))
+            );
+        }
+    }
+
+    #[test]
+    fn remaining_estimate_counts_shared_dependencies_once() {
+        // a diamond, where `top` depends on `left` and `right`, which both depend on `bottom`
+        let mut db = Database::default();
+        let mut create_task = |estimate: Option<&str>| {
+            add_task(&mut db, "task", |task| {
+                task.estimate = estimate.map(|e| e.parse().unwrap());
+            })
+        };
+        let top = create_task(Some("1h"));
+        let left = create_task(Some("2h"));
+        let right = create_task(None);
+        let bottom = create_task(Some("1d"));
+        db.add_dependency(&top, &left).unwrap();
+        db.add_dependency(&top, &right).unwrap();
+        db.add_dependency(&left, &bottom).unwrap();
+        db.add_dependency(&right, &bottom).unwrap();
+
+        let remaining = db.remaining_estimate(&top);
+        assert_eq!(remaining.total.to_string(), "1d 3h");
+        assert_eq!(remaining.unestimated_count, 1);
+
+        db[&bottom].time_completed = Some(db[&bottom].time_created);
+        assert_eq!(db.remaining_estimate(&top).total.to_string(), "3h");
+        assert_eq!(db.remaining_estimate(&bottom).total.to_string(), "0m");
+    }
+}
@@ -0,0 +1,190 @@
+//! Exports the task graph in the `GraphViz` DOT format.
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+};
+
+use petgraph::{stable_graph::NodeIndex, visit::EdgeRef, Direction};
+
+use super::{Database, Task, TaskId};
+use crate::errors::TaskError;
+
+/// Options for [`Database::to_dot`].
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// Whether to include completed tasks in the graph.
+    pub include_completed: bool,
+    /// If set, only this task and the tasks it (indirectly) depends on are exported.
+    pub root: Option<TaskId>,
+}
+
+impl Database {
+    /// Renders the task graph in the `GraphViz` DOT format. Edges point from a task to the tasks it
+    /// depends on.
+    ///
+    /// Returns an error if [`DotOptions::root`] refers to a task that does not exist.
+    pub fn to_dot(&self, options: &DotOptions) -> Result<String, TaskError> {
+        let mut nodes = match &options.root {
+            Some(root) => self.get_reachable_nodes(root, options.include_completed)?,
+            None => self.graph.node_indices().collect(),
+        };
+        if !options.include_completed {
+            nodes.retain(|&i| self.graph[i].time_completed.is_none());
+        }
+
+        let mut dot = String::new();
+        self.write_dot(&mut dot, &nodes)
+            .expect("writing to a string should not fail");
+        Ok(dot)
+    }
+
+    fn get_reachable_nodes(
+        &self,
+        root: &TaskId,
+        include_completed: bool,
+    ) -> Result<Vec<NodeIndex>, TaskError> {
+        let root_index = self
+            .get_node_index(root)
+            .ok_or_else(|| TaskError::NotFound(root.clone()))?;
+
+        // completed tasks are not traversed when excluded, so their dependencies are not exported
+        let mut visited = HashSet::from([root_index]);
+        let mut stack = vec![root_index];
+        while let Some(index) = stack.pop() {
+            for neighbor in self.graph.neighbors_directed(index, Direction::Outgoing) {
+                let is_included =
+                    include_completed || self.graph[neighbor].time_completed.is_none();
+                if is_included && visited.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        // keep the order of the graph so the output is stable
+        Ok(self
+            .graph
+            .node_indices()
+            .filter(|i| visited.contains(i))
+            .collect())
+    }
+
+    fn write_dot(&self, out: &mut String, nodes: &[NodeIndex]) -> fmt::Result {
+        writeln!(out, "digraph td {{")?;
+        writeln!(out, "    rankdir=LR;")?;
+        writeln!(
+            out,
+            "    node [shape=box, style=\"rounded,filled\", fillcolor=white];"
+        )?;
+
+        for &index in nodes {
+            let task = &self.graph[index];
+            writeln!(
+                out,
+                "    {} [label={}{}];",
+                quote(&task.id.to_string()),
+                quote(&Self::dot_label(task)),
+                self.dot_style(task),
+            )?;
+        }
+
+        let included = nodes.iter().copied().collect::<HashSet<_>>();
+        for &index in nodes {
+            for edge in self.graph.edges_directed(index, Direction::Outgoing) {
+                if included.contains(&edge.target()) {
+                    writeln!(
+                        out,
+                        "    {} -> {};",
+                        quote(&self.graph[index].id.to_string()),
+                        quote(&self.graph[edge.target()].id.to_string()),
+                    )?;
+                }
+            }
+        }
+
+        writeln!(out, "}}")
+    }
+
+    fn dot_label(task: &Task) -> String {
+        if task.tags.is_empty() {
+            task.title.clone()
+        } else {
+            format!("{}\n{}", task.title, task.tags.join(", "))
+        }
+    }
+
+    fn dot_style(&self, task: &Task) -> &'static str {
+        if task.time_completed.is_some() {
+            ", fillcolor=gray90, fontcolor=gray50, color=gray50"
+        } else if task.is_started() {
+            ", fillcolor=lightyellow"
+        } else if self.has_unfinished_dependencies(task.id()) {
+            ", fillcolor=mistyrose, color=red"
+        } else {
+            ""
+        }
+    }
+}
+
+/// Quotes a string as a DOT identifier.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_utils::add_task;
+
+    #[test]
+    fn export_graph() {
+        let mut db = Database::default();
+        let a = add_task(&mut db, "say \"hi\"", |_| ());
+        let b = add_task(&mut db, "b", |_| ());
+        let c = add_task(&mut db, "c", |_| ());
+        db.add_dependency(&a, &b).unwrap();
+        db.add_dependency(&b, &c).unwrap();
+        db.add_tag(&b, "tag".into()).unwrap();
+        db[&c].time_completed = db[&c].time_created.into();
+
+        let dot = db.to_dot(&DotOptions::default()).unwrap();
+        assert!(dot.starts_with("digraph td {\n"));
+        assert!(dot.contains(&format!(
+            "\"{a}\" [label=\"say \\\"hi\\\"\", fillcolor=mistyrose"
+        )));
+        assert!(dot.contains(&format!("\"{b}\" [label=\"b\\ntag\"];")));
+        assert!(dot.contains(&format!("\"{a}\" -> \"{b}\";")));
+        assert!(!dot.contains(&c.to_string()));
+
+        let options = DotOptions {
+            include_completed: true,
+            root: Some(b.clone()),
+        };
+        let dot = db.to_dot(&options).unwrap();
+        assert!(!dot.contains(&a.to_string()));
+        assert!(dot.contains(&format!("\"{b}\" -> \"{c}\";")));
+        assert!(dot.contains(&format!("\"{c}\" [label=\"c\", fillcolor=gray90")));
+    }
+
+    #[test]
+    fn export_unknown_root() {
+        let db = Database::default();
+        let options = DotOptions {
+            root: Some("unknown".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(matches!(db.to_dot(&options), Err(TaskError::NotFound(_))));
+    }
+}
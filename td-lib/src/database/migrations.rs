@@ -0,0 +1,39 @@
+//! Upgrades database files from older versions to the current version.
+//!
+//! Not every change to the stored data needs a new version. Adding an optional field that is
+//! `#[serde(default)]` and skipped when empty, such as a due date, keeps existing files valid and
+//! is ignored by older versions of td, so it can be added to the current version directly. A new
+//! version and migration step are only needed when existing data changes meaning or moves, like
+//! the `P:n` tags becoming a priority field.
+
+use super::{v2, v3, v4, CURRENT_DATABASE_VERSION};
+use crate::errors::DatabaseReadError;
+
+/// A single migration step, which upgrades the data of a database file by 1 version.
+type MigrationStep = fn(serde_json::Value) -> Result<serde_json::Value, DatabaseReadError>;
+
+/// All migration steps, in order. The step at index `i` upgrades from version `i + 1` to `i + 2`.
+const MIGRATION_STEPS: &[MigrationStep] = &[
+    v2::migrate_from_v1,
+    v3::migrate_from_v2,
+    v4::migrate_from_v3,
+];
+
+// every version except the first one should have a migration step leading to it
+const _: () = assert!(MIGRATION_STEPS.len() + 1 == CURRENT_DATABASE_VERSION as usize);
+
+/// Upgrades the data of a database file with the given version to the current version.
+pub fn migrate(
+    version: u8,
+    mut data: serde_json::Value,
+) -> Result<serde_json::Value, DatabaseReadError> {
+    if version == 0 || version > CURRENT_DATABASE_VERSION {
+        return Err(DatabaseReadError::UnknownVersion(version));
+    }
+
+    for step in &MIGRATION_STEPS[version as usize - 1..] {
+        data = step(data)?;
+    }
+
+    Ok(data)
+}
@@ -1,12 +1,24 @@
 //! Types related to the task database.
 
+pub mod analysis;
 mod database_api;
 pub mod database_file;
+pub mod estimate;
+pub mod graphviz;
+mod migrations;
+pub mod operations;
+pub mod query;
+pub mod recurrence;
+pub mod sessions;
+pub mod statistics;
 mod v1;
+mod v2;
+mod v3;
+mod v4;
 
 use serde::{de::DeserializeOwned, Serialize};
 // NOTE: this import should import the current version of the database schema
-pub use v1::*;
+pub use v4::*;
 
 /// The current version of the database model.
 pub const CURRENT_DATABASE_VERSION: u8 = Database::VERSION;
@@ -15,13 +27,59 @@ trait DatabaseImpl: Default + Serialize + DeserializeOwned {
     const VERSION: u8;
 }
 
+/// Helpers for filling a database in tests.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::{Database, Task, TaskId};
+
+    /// Adds a task with the given title to the database, after letting `setup` change it. Returns
+    /// the id of the task.
+    pub fn add_task(db: &mut Database, title: &str, setup: impl FnOnce(&mut Task)) -> TaskId {
+        let mut task = Task::create_now(title.into());
+        setup(&mut task);
+        let id = task.id().clone();
+        db.add_task(task);
+        id
+    }
+
+    /// Adds `count` tasks titled `task 0`, `task 1` and so on. Returns their ids in that order.
+    pub fn add_tasks(db: &mut Database, count: usize) -> Vec<TaskId> {
+        (0..count)
+            .map(|i| add_task(db, &format!("task {i}"), |_| ()))
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     pub fn new_db_is_valid_json() {
-        let db = v1::Database::default();
+        let db = v4::Database::default();
         serde_json::to_value(db).expect("new database should always be valid json");
     }
+
+    #[test]
+    pub fn empty_description_is_not_serialized() {
+        let mut task = Task::create_now("title".into());
+        let json = serde_json::to_value(&task).unwrap();
+        assert!(json.get("description").is_none());
+
+        task.description = "first line\nsecond line".into();
+        let json = serde_json::to_value(&task).unwrap();
+        let task: Task = serde_json::from_value(json).unwrap();
+        assert_eq!(task.description, "first line\nsecond line");
+    }
+
+    #[test]
+    pub fn due_date_is_serialized_as_date() {
+        let mut task = Task::create_now("title".into());
+        task.due_date = Some(time::Date::from_calendar_date(2024, time::Month::March, 1).unwrap());
+        let json = serde_json::to_value(&task).unwrap();
+        assert_eq!(json["due_date"], "2024-03-01");
+
+        let deserialized: Task = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.due_date, task.due_date);
+    }
 }
@@ -0,0 +1,651 @@
+//! Changes to the database as typed operations, which can be recorded in an [`OperationLog`] and
+//! replayed later.
+
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+
+use super::{estimate::Estimate, recurrence::Recurrence, Database, Priority, Task, TaskId};
+use crate::errors::{DatabaseReadError, TaskError};
+
+/// A change to the database. See [`Database::apply`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    /// Adds a new task.
+    AddTask {
+        /// The task to add, including its id.
+        task: Task,
+    },
+    /// Changes the title of a task.
+    Rename {
+        /// The task to rename.
+        id: TaskId,
+        /// The new title.
+        title: String,
+    },
+    /// Changes the description of a task.
+    SetDescription {
+        /// The task to change.
+        id: TaskId,
+        /// The new description.
+        description: String,
+    },
+    /// Changes or clears the due date of a task.
+    SetDueDate {
+        /// The task to change.
+        id: TaskId,
+        /// The new due date.
+        due_date: Option<Date>,
+    },
+    /// Changes or clears how often a task repeats.
+    SetRecurrence {
+        /// The task to change.
+        id: TaskId,
+        /// The new recurrence.
+        recurrence: Option<Recurrence>,
+    },
+    /// Changes or clears the priority of a task.
+    SetPriority {
+        /// The task to change.
+        id: TaskId,
+        /// The new priority.
+        priority: Option<Priority>,
+    },
+    /// Changes or clears the estimate of a task.
+    SetEstimate {
+        /// The task to change.
+        id: TaskId,
+        /// The new estimate.
+        estimate: Option<Estimate>,
+    },
+    /// Adds a tag to a task.
+    AddTag {
+        /// The task to tag.
+        id: TaskId,
+        /// The tag to add.
+        tag: String,
+    },
+    /// Removes a tag from a task.
+    RemoveTag {
+        /// The task to change.
+        id: TaskId,
+        /// The tag to remove.
+        tag: String,
+    },
+    /// Renames a tag on every task that has it. See [`Database::rename_tag`].
+    RenameTag {
+        /// The current name of the tag.
+        old_name: String,
+        /// The new name of the tag.
+        new_name: String,
+    },
+    /// Makes one task depend on another.
+    AddDependency {
+        /// The dependent task.
+        from: TaskId,
+        /// The task it depends on.
+        to: TaskId,
+    },
+    /// Removes the dependency of one task on another.
+    RemoveDependency {
+        /// The dependent task.
+        from: TaskId,
+        /// The task it depends on.
+        to: TaskId,
+    },
+    /// Marks a task as completed. See [`Database::complete_task`].
+    MarkDone {
+        /// The task to complete.
+        id: TaskId,
+        /// When the task was completed.
+        #[serde(with = "time::serde::rfc3339")]
+        completed: OffsetDateTime,
+        /// The id of the next instance, if the task repeats. Stored so replaying the operation
+        /// creates the same task.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next_id: Option<TaskId>,
+    },
+    /// Marks a completed task as not completed. See [`Database::reopen_task`].
+    Reopen {
+        /// The task to reopen.
+        id: TaskId,
+    },
+    /// Starts the timer of a task. See [`Task::start_timer`].
+    StartTimer {
+        /// The task to start.
+        id: TaskId,
+        /// When the timer was started.
+        #[serde(with = "time::serde::rfc3339")]
+        started: OffsetDateTime,
+    },
+    /// Stops the timer of a task. See [`Task::stop_timer`].
+    StopTimer {
+        /// The task to stop.
+        id: TaskId,
+        /// When the timer was stopped.
+        #[serde(with = "time::serde::rfc3339")]
+        stopped: OffsetDateTime,
+    },
+    /// Removes a task and its dependencies.
+    Delete {
+        /// The task to remove.
+        id: TaskId,
+    },
+    /// Puts tasks and dependencies back the way they were in another version of the database, such
+    /// as when undoing changes. Dependencies are restored without checking for cycles.
+    Restore {
+        /// The tasks to add, or to replace the task with the same id.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tasks: Vec<Task>,
+        /// The tasks to remove.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        removed_tasks: Vec<TaskId>,
+        /// The dependencies to add, from the dependent task to its dependency.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        added_dependencies: Vec<(TaskId, TaskId)>,
+        /// The dependencies to remove, from the dependent task to its dependency.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        removed_dependencies: Vec<(TaskId, TaskId)>,
+    },
+}
+
+/// An operation in an [`OperationLog`], along with who applied it and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// When the operation was applied.
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    /// Who applied the operation.
+    pub author: String,
+    /// The operation that was applied.
+    #[serde(flatten)]
+    pub operation: Operation,
+}
+
+/// A file that operations are appended to as they are applied, one json line per operation.
+#[derive(Debug, Clone)]
+pub struct OperationLog {
+    path: PathBuf,
+    author: String,
+}
+
+impl Operation {
+    /// Creates an operation that completes a task at the given time. If the task repeats, this
+    /// also picks the id of its next instance.
+    #[must_use]
+    pub fn mark_done(task: &Task, time: OffsetDateTime) -> Self {
+        Self::MarkDone {
+            id: task.id.clone(),
+            completed: time,
+            next_id: task.recurrence.map(|_| TaskId::new()),
+        }
+    }
+
+    /// Gets the id of the task this operation creates, if any.
+    #[must_use]
+    pub fn created_task(&self) -> Option<&TaskId> {
+        match self {
+            Self::AddTask { task } => Some(&task.id),
+            Self::MarkDone { next_id, .. } => next_id.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AddTask { task } => write!(f, "add task {} '{}'", task.id, task.title),
+            Self::Rename { id, title } => write!(f, "rename {id} to '{title}'"),
+            Self::SetDescription { id, .. } => write!(f, "change description of {id}"),
+            Self::SetDueDate { id, due_date } => match due_date {
+                Some(date) => write!(f, "set due date of {id} to {date}"),
+                None => write!(f, "clear due date of {id}"),
+            },
+            Self::SetRecurrence { id, recurrence } => match recurrence {
+                Some(recurrence) => write!(f, "make {id} repeat {recurrence}"),
+                None => write!(f, "make {id} not repeat"),
+            },
+            Self::SetPriority { id, priority } => match priority {
+                Some(priority) => write!(f, "set priority of {id} to {priority}"),
+                None => write!(f, "clear priority of {id}"),
+            },
+            Self::SetEstimate { id, estimate } => match estimate {
+                Some(estimate) => write!(f, "set estimate of {id} to {estimate}"),
+                None => write!(f, "clear estimate of {id}"),
+            },
+            Self::AddTag { id, tag } => write!(f, "add tag '{tag}' to {id}"),
+            Self::RemoveTag { id, tag } => write!(f, "remove tag '{tag}' from {id}"),
+            Self::RenameTag { old_name, new_name } => {
+                write!(f, "rename tag '{old_name}' to '{new_name}'")
+            }
+            Self::AddDependency { from, to } => write!(f, "add dependency {from} -> {to}"),
+            Self::RemoveDependency { from, to } => write!(f, "remove dependency {from} -> {to}"),
+            Self::MarkDone { id, .. } => write!(f, "mark {id} as done"),
+            Self::Reopen { id } => write!(f, "reopen {id}"),
+            Self::StartTimer { id, .. } => write!(f, "start timer of {id}"),
+            Self::StopTimer { id, .. } => write!(f, "stop timer of {id}"),
+            Self::Delete { id } => write!(f, "delete {id}"),
+            Self::Restore {
+                tasks,
+                removed_tasks,
+                ..
+            } => write!(
+                f,
+                "restore {} tasks and remove {} tasks",
+                tasks.len(),
+                removed_tasks.len()
+            ),
+        }
+    }
+}
+
+impl Database {
+    /// Applies an operation to the database. Returns whether anything changed, which is not the
+    /// case if the operation was already applied before, such as adding a tag the task already
+    /// has.
+    ///
+    /// If the operation refers to a task that does not exist or would introduce a dependency
+    /// cycle, no changes are made and an error is returned.
+    pub fn apply(&mut self, operation: &Operation) -> Result<bool, TaskError> {
+        match operation {
+            Operation::AddTask { task } => {
+                if self.contains_task(&task.id) {
+                    return Ok(false);
+                }
+                self.add_task(task.clone());
+                Ok(true)
+            }
+            Operation::Rename { id, title } => {
+                let task = self
+                    .get_mut(id)
+                    .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+                if &task.title == title {
+                    return Ok(false);
+                }
+                task.title.clone_from(title);
+                Ok(true)
+            }
+            Operation::SetDescription { id, description } => {
+                self.set_field(id, description.clone(), |task| &mut task.description)
+            }
+            Operation::SetDueDate { id, due_date } => {
+                self.set_field(id, *due_date, |task| &mut task.due_date)
+            }
+            Operation::SetRecurrence { id, recurrence } => {
+                self.set_field(id, *recurrence, |task| &mut task.recurrence)
+            }
+            Operation::SetPriority { id, priority } => {
+                self.set_field(id, *priority, |task| &mut task.priority)
+            }
+            Operation::SetEstimate { id, estimate } => {
+                self.set_field(id, *estimate, |task| &mut task.estimate)
+            }
+            Operation::AddTag { id, tag } => self.add_tag(id, tag.clone()),
+            Operation::RemoveTag { id, tag } => self.remove_tag(id, tag),
+            Operation::RenameTag { old_name, new_name } => {
+                Ok(self.rename_tag(old_name, new_name) > 0)
+            }
+            Operation::AddDependency { from, to } => {
+                if self.try_get_dependencies(from)?.any(|t| &t.id == to) {
+                    return Ok(false);
+                }
+                self.try_add_dependency(from, to)?;
+                Ok(true)
+            }
+            Operation::RemoveDependency { from, to } => {
+                self.ensure_task_exists(from)?;
+                self.ensure_task_exists(to)?;
+                Ok(self.remove_dependency(from, to))
+            }
+            Operation::MarkDone {
+                id,
+                completed,
+                next_id,
+            } => {
+                let task = self
+                    .get(id)
+                    .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+                if task.time_completed.is_some() {
+                    return Ok(false);
+                }
+                // operations that did not expect the task to repeat still create the next instance
+                let next_id = next_id.clone().unwrap_or_else(TaskId::new);
+                self.complete_task_with_next_id(id, *completed, next_id)?;
+                Ok(true)
+            }
+            Operation::Reopen { id } => self.reopen_task(id),
+            Operation::StartTimer { id, started } => {
+                let task = self
+                    .get_mut(id)
+                    .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+                // a session that starts at the same time means the timer was started by this
+                // operation, even if it was stopped since
+                let already_started = task.sessions.iter().any(|s| s.start == *started);
+                Ok(!already_started && task.start_timer(*started))
+            }
+            Operation::StopTimer { id, stopped } => {
+                let task = self
+                    .get_mut(id)
+                    .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+                Ok(task.stop_timer(*stopped))
+            }
+            Operation::Delete { id } => {
+                self.ensure_task_exists(id)?;
+                self.remove_task(id);
+                Ok(true)
+            }
+            Operation::Restore {
+                tasks,
+                removed_tasks,
+                added_dependencies,
+                removed_dependencies,
+            } => {
+                // check the dependencies first, so nothing is changed if one can not be restored
+                for id in added_dependencies.iter().flat_map(|(from, to)| [from, to]) {
+                    let restored = tasks.iter().any(|t| &t.id == id);
+                    if removed_tasks.contains(id) || !(restored || self.contains_task(id)) {
+                        return Err(TaskError::NotFound(id.clone()));
+                    }
+                }
+
+                let mut changed = false;
+                for (from, to) in removed_dependencies {
+                    changed |= self.remove_dependency(from, to);
+                }
+                for id in removed_tasks {
+                    if self.contains_task(id) {
+                        self.remove_task(id);
+                        changed = true;
+                    }
+                }
+                for task in tasks {
+                    match self.get_mut(&task.id) {
+                        Some(existing) if existing == task => {}
+                        Some(existing) => {
+                            existing.clone_from(task);
+                            changed = true;
+                        }
+                        None => {
+                            self.add_task(task.clone());
+                            changed = true;
+                        }
+                    }
+                }
+                for (from, to) in added_dependencies {
+                    changed |= self.restore_dependency(from, to);
+                }
+                Ok(changed)
+            }
+        }
+    }
+
+    /// Sets one field of a task, returning whether it changed.
+    fn set_field<T: PartialEq>(
+        &mut self,
+        id: &TaskId,
+        value: T,
+        field: impl FnOnce(&mut Task) -> &mut T,
+    ) -> Result<bool, TaskError> {
+        let task = self
+            .get_mut(id)
+            .ok_or_else(|| TaskError::NotFound(id.clone()))?;
+        let field = field(task);
+        if *field == value {
+            return Ok(false);
+        }
+        *field = value;
+        Ok(true)
+    }
+}
+
+impl OperationLog {
+    /// Creates a log that appends to the file at the given path, attributing every operation to
+    /// `author`. The file is created when the first operation is appended.
+    #[must_use]
+    pub fn new(path: PathBuf, author: String) -> Self {
+        Self { path, author }
+    }
+
+    /// Gets the path of the log file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends an operation that was applied at the given time to the log.
+    pub fn append(
+        &self,
+        operation: &Operation,
+        time: OffsetDateTime,
+    ) -> Result<(), DatabaseReadError> {
+        let entry = LogEntry {
+            time,
+            author: self.author.clone(),
+            operation: operation.clone(),
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Reads every entry in the log file at the given path, oldest first.
+    pub fn read(path: &Path) -> Result<Vec<LogEntry>, DatabaseReadError> {
+        let file = std::fs::read_to_string(path)?;
+        let entries = file
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_operations() {
+        let mut db = Database::default();
+        let a = Task::create_now("a".into());
+        let b = Task::create_now("b".into());
+        let (a_id, b_id) = (a.id.clone(), b.id.clone());
+
+        assert_eq!(db.apply(&Operation::AddTask { task: a.clone() }), Ok(true));
+        assert_eq!(db.apply(&Operation::AddTask { task: a }), Ok(false));
+        assert_eq!(db.apply(&Operation::AddTask { task: b }), Ok(true));
+
+        let depend = Operation::AddDependency {
+            from: a_id.clone(),
+            to: b_id.clone(),
+        };
+        assert_eq!(db.apply(&depend), Ok(true));
+        assert_eq!(db.apply(&depend), Ok(false));
+        let cycle = Operation::AddDependency {
+            from: b_id.clone(),
+            to: a_id.clone(),
+        };
+        assert!(matches!(
+            db.apply(&cycle),
+            Err(TaskError::DependencyCycle(_))
+        ));
+
+        let rename = Operation::Rename {
+            id: a_id.clone(),
+            title: "renamed".into(),
+        };
+        assert_eq!(db.apply(&rename), Ok(true));
+        assert_eq!(db[&a_id].title, "renamed");
+
+        let now = OffsetDateTime::now_utc();
+        let start = Operation::StartTimer {
+            id: b_id.clone(),
+            started: now,
+        };
+        assert_eq!(db.apply(&start), Ok(true));
+        assert_eq!(db.apply(&start), Ok(false));
+        let stop = Operation::StopTimer {
+            id: b_id.clone(),
+            stopped: now,
+        };
+        assert_eq!(db.apply(&stop), Ok(true));
+        assert_eq!(db.apply(&stop), Ok(false));
+        assert_eq!(db.apply(&start), Ok(false));
+
+        let done = Operation::mark_done(&db[&b_id], now);
+        assert_eq!(done.created_task(), None);
+        assert_eq!(db.apply(&done), Ok(true));
+        assert_eq!(db.apply(&done), Ok(false));
+        assert!(db[&b_id].time_completed.is_some());
+
+        assert_eq!(db.apply(&Operation::Delete { id: b_id.clone() }), Ok(true));
+        assert_eq!(
+            db.apply(&Operation::Delete { id: b_id.clone() }),
+            Err(TaskError::NotFound(b_id))
+        );
+        assert_eq!(db.get_dependencies(&a_id).count(), 0);
+    }
+
+    #[test]
+    fn apply_edit_operations() {
+        let mut db = Database::default();
+        let task = Task::create_now("task".into());
+        let id = task.id.clone();
+        db.apply(&Operation::AddTask { task }).unwrap();
+
+        let edits = [
+            Operation::SetDescription {
+                id: id.clone(),
+                description: "details".into(),
+            },
+            Operation::SetDueDate {
+                id: id.clone(),
+                due_date: Some(Date::MIN),
+            },
+            Operation::SetRecurrence {
+                id: id.clone(),
+                recurrence: Some("weekly".parse().unwrap()),
+            },
+            Operation::SetPriority {
+                id: id.clone(),
+                priority: Some(Priority::HIGHEST),
+            },
+            Operation::SetEstimate {
+                id: id.clone(),
+                estimate: Some("2h".parse().unwrap()),
+            },
+            Operation::AddTag {
+                id: id.clone(),
+                tag: "work".into(),
+            },
+            Operation::RenameTag {
+                old_name: "work".into(),
+                new_name: "job".into(),
+            },
+        ];
+        for edit in &edits {
+            assert_eq!(db.apply(edit), Ok(true), "{edit}");
+            assert_eq!(db.apply(edit), Ok(false), "{edit}");
+        }
+        let task = &db[&id];
+        assert_eq!(task.description, "details");
+        assert_eq!(task.due_date, Some(Date::MIN));
+        assert_eq!(task.priority, Some(Priority::HIGHEST));
+        assert!(task.recurrence.is_some() && task.estimate.is_some());
+        assert_eq!(task.tags, ["job"]);
+
+        let remove_tag = Operation::RemoveTag {
+            id: id.clone(),
+            tag: "job".into(),
+        };
+        assert_eq!(db.apply(&remove_tag), Ok(true));
+        assert_eq!(db.apply(&remove_tag), Ok(false));
+
+        let done = Operation::mark_done(&db[&id], OffsetDateTime::now_utc());
+        db.apply(&done).unwrap();
+        let reopen = Operation::Reopen { id: id.clone() };
+        assert_eq!(db.apply(&reopen), Ok(true));
+        assert_eq!(db.apply(&reopen), Ok(false));
+        assert_eq!(db.get_all_tasks().count(), 1);
+    }
+
+    #[test]
+    fn restore_checks_dependencies_first() {
+        let mut db = Database::default();
+        let a = Task::create_now("a".into());
+        let b = Task::create_now("b".into());
+        let (a_id, b_id) = (a.id.clone(), b.id.clone());
+        db.add_task(a);
+
+        let restore = Operation::Restore {
+            tasks: vec![b],
+            removed_tasks: vec![a_id.clone()],
+            added_dependencies: vec![(b_id.clone(), a_id.clone())],
+            removed_dependencies: vec![],
+        };
+        assert_eq!(db.apply(&restore), Err(TaskError::NotFound(a_id.clone())));
+        assert!(db.contains_task(&a_id));
+        assert!(!db.contains_task(&b_id));
+    }
+
+    #[test]
+    fn replaying_recurring_task_creates_same_instance() {
+        let mut task = Task::create_now("chore".into());
+        task.recurrence = Some("daily".parse().unwrap());
+        let done = Operation::mark_done(&task, OffsetDateTime::now_utc());
+        assert!(done.created_task().is_some());
+        let operations = [Operation::AddTask { task }, done];
+
+        let replay = || {
+            let mut db = Database::default();
+            for operation in &operations {
+                db.apply(operation).unwrap();
+            }
+            let mut ids = db.get_all_tasks().map(|t| t.id.clone()).collect::<Vec<_>>();
+            ids.sort_by_key(ToString::to_string);
+            ids
+        };
+        assert_eq!(replay().len(), 2);
+        assert_eq!(replay(), replay());
+    }
+
+    #[test]
+    fn log_round_trip() {
+        let path = std::env::temp_dir().join(format!("td-log-test-{}.jsonl", nanoid::nanoid!()));
+        let log = OperationLog::new(path.clone(), "alice".into());
+        let task = Task::create_now("task".into());
+        let operations = [
+            Operation::AddTag {
+                id: task.id.clone(),
+                tag: "work".into(),
+            },
+            Operation::mark_done(&task, task.time_created),
+            Operation::AddTask { task },
+        ];
+        for operation in &operations {
+            log.append(operation, OffsetDateTime::UNIX_EPOCH).unwrap();
+        }
+
+        let entries = OperationLog::read(&path).unwrap();
+        _ = std::fs::remove_file(path);
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.author == "alice"));
+        assert_eq!(entries[0].time, OffsetDateTime::UNIX_EPOCH);
+        let read = entries.into_iter().map(|e| e.operation).collect::<Vec<_>>();
+        assert_eq!(read, operations);
+    }
+}
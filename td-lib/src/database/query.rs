@@ -0,0 +1,334 @@
+//! A small query language to filter tasks, such as `tag:backend -is:done "free text"`.
+//!
+//! A query consists of whitespace-separated terms, all of which must match. A term can be negated
+//! by prefixing it with `-`, and values containing spaces can be quoted. The supported terms are:
+//!
+//! - `text`: the title or description contains the text, ignoring case
+//! - `tag:name`: the task has the given tag
+//! - `is:open`, `is:started`, `is:done`, `is:blocked` or `is:actionable`: the status of the task
+//! - `created:2024-01-01`: the task was created on the given day. The date can be prefixed with
+//!   `>`, `>=`, `<` or `<=` to match a range of days.
+//! - `depends-on:ID`: the task directly depends on the task with the given id
+
+use std::{cmp::Ordering, ops::Range, str::FromStr};
+
+use time::{format_description, Date};
+
+use super::{Database, Task, TaskId};
+use crate::errors::{QueryParseError, QueryParseErrorKind};
+
+/// A parsed filter query. See the [module-level documentation](self) for the syntax.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    negated: bool,
+    filter: Filter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    /// A lowercase string to search for.
+    Text(String),
+    Tag(String),
+    Status(Status),
+    Created(Vec<Ordering>, Date),
+    DependsOn(TaskId),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Open,
+    Started,
+    Done,
+    Blocked,
+    Actionable,
+}
+
+/// A single term in the query text, before it is interpreted.
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+    span: Range<usize>,
+}
+
+impl Query {
+    /// Parses a query. An empty query matches every task.
+    pub fn parse(text: &str) -> Result<Self, QueryParseError> {
+        let terms = tokenize(text)?
+            .into_iter()
+            .filter(|token| token.key.is_some() || !token.value.is_empty())
+            .map(Term::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    /// Returns whether this query has no terms, meaning that it matches every task.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns whether the given task matches this query.
+    ///
+    /// Panics if the task is not in the given database.
+    #[must_use]
+    pub fn matches(&self, database: &Database, task: &Task) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.filter.matches(database, task) != term.negated)
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Term {
+    fn parse(token: Token) -> Result<Self, QueryParseError> {
+        let error = |kind| QueryParseError {
+            kind,
+            span: token.span.clone(),
+        };
+        let invalid_value = |expected| {
+            error(QueryParseErrorKind::InvalidValue {
+                filter: token.key.clone().unwrap_or_default(),
+                value: token.value.clone(),
+                expected,
+            })
+        };
+
+        let Some(key) = &token.key else {
+            return Ok(Self {
+                negated: token.negated,
+                filter: Filter::Text(token.value.to_lowercase()),
+            });
+        };
+
+        if !matches!(key.as_str(), "tag" | "is" | "created" | "depends-on") {
+            return Err(error(QueryParseErrorKind::UnknownFilter(key.clone())));
+        }
+        if token.value.is_empty() {
+            return Err(error(QueryParseErrorKind::MissingValue(key.clone())));
+        }
+
+        let filter = match key.as_str() {
+            "tag" => Filter::Tag(token.value.clone()),
+            "is" => Filter::Status(match token.value.as_str() {
+                "open" => Status::Open,
+                "started" => Status::Started,
+                "done" | "completed" => Status::Done,
+                "blocked" => Status::Blocked,
+                "actionable" => Status::Actionable,
+                _ => return Err(invalid_value("open, started, done, blocked or actionable")),
+            }),
+            "created" => {
+                let (orderings, date) = match token.value.as_bytes() {
+                    [b'>', b'=', ..] => {
+                        (vec![Ordering::Greater, Ordering::Equal], &token.value[2..])
+                    }
+                    [b'<', b'=', ..] => (vec![Ordering::Less, Ordering::Equal], &token.value[2..]),
+                    [b'>', ..] => (vec![Ordering::Greater], &token.value[1..]),
+                    [b'<', ..] => (vec![Ordering::Less], &token.value[1..]),
+                    _ => (vec![Ordering::Equal], token.value.as_str()),
+                };
+                let format = format_description::parse("[year]-[month]-[day]")
+                    .expect("valid hardcoded date format");
+                let date = Date::parse(date, &format)
+                    .map_err(|_| invalid_value("a date such as >=2024-01-31"))?;
+                Filter::Created(orderings, date)
+            }
+            "depends-on" => Filter::DependsOn(token.value.parse().expect("infallible")),
+            _ => unreachable!("filter names are checked above"),
+        };
+
+        Ok(Self {
+            negated: token.negated,
+            filter,
+        })
+    }
+}
+
+impl Filter {
+    fn matches(&self, database: &Database, task: &Task) -> bool {
+        match self {
+            Self::Text(text) => {
+                task.title.to_lowercase().contains(text)
+                    || task.description.to_lowercase().contains(text)
+            }
+            Self::Tag(tag) => task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)),
+            Self::Status(status) => {
+                let is_done = task.time_completed.is_some();
+                match status {
+                    Status::Open => !is_done && !task.is_started(),
+                    Status::Started => !is_done && task.is_started(),
+                    Status::Done => is_done,
+                    Status::Blocked => !is_done && database.has_unfinished_dependencies(task.id()),
+                    Status::Actionable => {
+                        !is_done && !database.has_unfinished_dependencies(task.id())
+                    }
+                }
+            }
+            Self::Created(orderings, date) => {
+                orderings.contains(&task.time_created.date().cmp(date))
+            }
+            Self::DependsOn(id) => database
+                .get_dependencies(task.id())
+                .any(|dep| dep.id() == id),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, QueryParseError> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // a lone `-` is treated as text
+        let mut negated = false;
+        if c == '-' {
+            let mut lookahead = chars.clone();
+            lookahead.next();
+            if lookahead.peek().is_some_and(|(_, c)| !c.is_whitespace()) {
+                negated = true;
+                chars.next();
+            }
+        }
+
+        let mut key = None;
+        let mut value = String::new();
+        let mut in_quotes = false;
+        let mut was_quoted = false;
+        let mut end = text.len();
+        for (i, c) in chars.by_ref() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    was_quoted = true;
+                }
+                ':' if !in_quotes && !was_quoted && key.is_none() && is_filter_name(&value) => {
+                    key = Some(std::mem::take(&mut value));
+                }
+                c if c.is_whitespace() && !in_quotes => {
+                    end = i;
+                    break;
+                }
+                c => value.push(c),
+            }
+        }
+
+        if in_quotes {
+            return Err(QueryParseError {
+                kind: QueryParseErrorKind::UnterminatedQuote,
+                span: start..text.len(),
+            });
+        }
+
+        tokens.push(Token {
+            negated,
+            key,
+            value,
+            span: start..end,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn is_filter_name(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_utils::add_task;
+
+    fn matching_titles(db: &Database, query: &str) -> Vec<String> {
+        let query = Query::parse(query).unwrap();
+        let mut titles = db
+            .get_all_tasks()
+            .filter(|t| query.matches(db, t))
+            .map(|t| t.title.clone())
+            .collect::<Vec<_>>();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn filter_tasks() {
+        let mut db = Database::default();
+        let api = add_task(&mut db, "Write API", |t| t.tags = vec!["backend".into()]);
+        let docs = add_task(&mut db, "Write API docs", |t| t.tags = vec!["docs".into()]);
+        let deploy = add_task(&mut db, "Deploy", |t| {
+            t.tags = vec!["backend".into(), "blocked".into()];
+        });
+        db.add_dependency(&docs, &api).unwrap();
+        db.add_dependency(&deploy, &api).unwrap();
+        let time_created = db[&api].time_created;
+        db[&api].start_timer(time_created);
+
+        assert_eq!(matching_titles(&db, "").len(), 3);
+        assert_eq!(
+            matching_titles(&db, "tag:backend -tag:blocked"),
+            ["Write API"]
+        );
+        assert_eq!(matching_titles(&db, "\"api docs\""), ["Write API docs"]);
+        assert_eq!(matching_titles(&db, "is:started"), ["Write API"]);
+        assert_eq!(
+            matching_titles(&db, "is:blocked"),
+            ["Deploy", "Write API docs"]
+        );
+        assert_eq!(
+            matching_titles(&db, &format!("depends-on:{api} write")),
+            ["Write API docs"]
+        );
+        assert_eq!(matching_titles(&db, "created:>2000-01-01").len(), 3);
+        assert_eq!(matching_titles(&db, "created:<=2000-01-01").len(), 0);
+
+        db[&api].time_completed = Some(db[&api].time_created);
+        assert_eq!(
+            matching_titles(&db, "is:actionable"),
+            ["Deploy", "Write API docs"]
+        );
+        assert_eq!(matching_titles(&db, "-is:done 12:30").len(), 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |query| Query::parse(query).unwrap_err();
+
+        assert_eq!(error("a \"b").kind, QueryParseErrorKind::UnterminatedQuote);
+        assert_eq!(error("a \"b").span, 2..4);
+        assert_eq!(
+            error("tag:a foo:bar").kind,
+            QueryParseErrorKind::UnknownFilter("foo".into())
+        );
+        assert_eq!(error("tag:a foo:bar").span, 6..13);
+        assert_eq!(
+            error("-tag:").kind,
+            QueryParseErrorKind::MissingValue("tag".into())
+        );
+        assert!(matches!(
+            error("is:sleeping").kind,
+            QueryParseErrorKind::InvalidValue { .. }
+        ));
+        assert!(matches!(
+            error("created:yesterday").kind,
+            QueryParseErrorKind::InvalidValue { .. }
+        ));
+    }
+}
@@ -0,0 +1,243 @@
+//! Recurrence rules for tasks that repeat, such as weekly chores.
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month};
+
+use crate::errors::RecurrenceParseError;
+
+/// How often a task repeats. When a recurring task is completed, the next instance is created with
+/// a due date based on this rule.
+///
+/// Recurrences can be parsed from text such as `daily`, `weekly`, `monthly`, `every 3 days` or
+/// `2w`, optionally followed by `after completion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// The amount of units between instances.
+    pub interval: u32,
+    /// The unit of the interval.
+    pub unit: RecurrenceUnit,
+    /// What the due date of the next instance is based on.
+    pub anchor: RecurrenceAnchor,
+}
+
+/// The unit of a [`Recurrence`] interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurrenceUnit {
+    /// A day.
+    Days,
+    /// 7 days.
+    Weeks,
+    /// A calendar month. If the day does not exist in the month, the last day of the month is used.
+    Months,
+}
+
+/// What the due date of the next instance of a [`Recurrence`] is based on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecurrenceAnchor {
+    /// The next instance is due one interval after the previous due date, regardless of when the
+    /// task was completed. Occurrences that have already passed are skipped.
+    Schedule,
+    /// The next instance is due one interval after the previous instance was completed.
+    Completion,
+}
+
+impl Recurrence {
+    /// Gets the due date of the next instance of a task, given the due date of the previous
+    /// instance and the day it was completed.
+    #[must_use]
+    pub fn next_due_date(&self, previous_due_date: Option<Date>, completed: Date) -> Date {
+        match (self.anchor, previous_due_date) {
+            (RecurrenceAnchor::Schedule, Some(due_date)) => {
+                let mut next = self.advance(due_date);
+                while next <= completed && next < Date::MAX {
+                    next = self.advance(next);
+                }
+                next
+            }
+            _ => self.advance(completed),
+        }
+    }
+
+    fn advance(&self, date: Date) -> Date {
+        // an interval of 0 is not valid, but could be in a hand-edited database file
+        let interval = self.interval.max(1);
+        match self.unit {
+            RecurrenceUnit::Days => date.checked_add(Duration::days(interval.into())),
+            RecurrenceUnit::Weeks => date.checked_add(Duration::weeks(interval.into())),
+            RecurrenceUnit::Months => add_months(date, interval),
+        }
+        .unwrap_or(Date::MAX)
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.interval, self.unit) {
+            (1, RecurrenceUnit::Days) => write!(f, "daily")?,
+            (1, RecurrenceUnit::Weeks) => write!(f, "weekly")?,
+            (1, RecurrenceUnit::Months) => write!(f, "monthly")?,
+            (interval, RecurrenceUnit::Days) => write!(f, "every {interval} days")?,
+            (interval, RecurrenceUnit::Weeks) => write!(f, "every {interval} weeks")?,
+            (interval, RecurrenceUnit::Months) => write!(f, "every {interval} months")?,
+        }
+        if self.anchor == RecurrenceAnchor::Completion {
+            write!(f, " after completion")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = RecurrenceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || RecurrenceParseError(s.to_string());
+        let lowercase = s.trim().to_lowercase();
+
+        let (rule, anchor) = match lowercase.strip_suffix(" after completion") {
+            Some(rule) => (rule.trim_end(), RecurrenceAnchor::Completion),
+            None => (lowercase.as_str(), RecurrenceAnchor::Schedule),
+        };
+
+        let (interval, unit) = match rule.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["daily"] => (1, RecurrenceUnit::Days),
+            ["weekly"] => (1, RecurrenceUnit::Weeks),
+            ["monthly"] => (1, RecurrenceUnit::Months),
+            ["every", unit] => (1, parse_unit(unit).ok_or_else(error)?),
+            ["every", interval, unit] => (
+                interval.parse().map_err(|_| error())?,
+                parse_unit(unit).ok_or_else(error)?,
+            ),
+            [short] => {
+                let (unit_index, _) = short.char_indices().next_back().ok_or_else(error)?;
+                let (interval, unit) = short.split_at(unit_index);
+                (
+                    interval.parse().map_err(|_| error())?,
+                    parse_unit(unit).ok_or_else(error)?,
+                )
+            }
+            _ => return Err(error()),
+        };
+
+        if interval == 0 {
+            return Err(error());
+        }
+
+        Ok(Self {
+            interval,
+            unit,
+            anchor,
+        })
+    }
+}
+
+fn parse_unit(text: &str) -> Option<RecurrenceUnit> {
+    match text {
+        "d" | "day" | "days" => Some(RecurrenceUnit::Days),
+        "w" | "week" | "weeks" => Some(RecurrenceUnit::Weeks),
+        "m" | "month" | "months" => Some(RecurrenceUnit::Months),
+        _ => None,
+    }
+}
+
+fn add_months(date: Date, months: u32) -> Option<Date> {
+    let month_index = i64::from(date.year()) * 12 + i64::from(u8::from(date.month()) - 1);
+    let month_index = month_index + i64::from(months);
+    let year = i32::try_from(month_index.div_euclid(12)).ok()?;
+    let month = Month::try_from(u8::try_from(month_index.rem_euclid(12) + 1).ok()?).ok()?;
+    let day = date.day().min(month.length(year));
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_recurrences() {
+        let parse = |text: &str| text.parse::<Recurrence>();
+        let recurrence = |interval, unit, anchor| Recurrence {
+            interval,
+            unit,
+            anchor,
+        };
+
+        assert_eq!(
+            parse("Weekly"),
+            Ok(recurrence(
+                1,
+                RecurrenceUnit::Weeks,
+                RecurrenceAnchor::Schedule
+            ))
+        );
+        assert_eq!(
+            parse("every 3 days after completion"),
+            Ok(recurrence(
+                3,
+                RecurrenceUnit::Days,
+                RecurrenceAnchor::Completion
+            ))
+        );
+        assert_eq!(
+            parse("2m"),
+            Ok(recurrence(
+                2,
+                RecurrenceUnit::Months,
+                RecurrenceAnchor::Schedule
+            ))
+        );
+
+        for text in ["daily", "every 2 weeks", "monthly after completion"] {
+            assert_eq!(parse(text).unwrap().to_string(), text);
+        }
+        for invalid in ["", "yearly", "every 0 days", "every day after", "3x", "3é"] {
+            assert_eq!(parse(invalid), Err(RecurrenceParseError(invalid.into())));
+        }
+    }
+
+    #[test]
+    fn next_due_date() {
+        let weekly: Recurrence = "weekly".parse().unwrap();
+        let weekly_after_completion: Recurrence = "weekly after completion".parse().unwrap();
+        let monthly: Recurrence = "monthly".parse().unwrap();
+
+        // completed early
+        let due = date(Month::March, 8);
+        let completed = date(Month::March, 6);
+        assert_eq!(
+            weekly.next_due_date(Some(due), completed),
+            date(Month::March, 15)
+        );
+        assert_eq!(
+            weekly_after_completion.next_due_date(Some(due), completed),
+            date(Month::March, 13)
+        );
+
+        // completed late, the missed week is skipped
+        let completed = date(Month::March, 16);
+        assert_eq!(
+            weekly.next_due_date(Some(due), completed),
+            date(Month::March, 22)
+        );
+
+        // without a previous due date, the schedule starts at completion
+        assert_eq!(
+            weekly.next_due_date(None, completed),
+            date(Month::March, 23)
+        );
+
+        // the day is clamped to the end of the month
+        assert_eq!(
+            monthly.next_due_date(Some(date(Month::January, 31)), date(Month::January, 20)),
+            date(Month::February, 29)
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Time tracking, by recording the sessions in which tasks were worked on.
+
+use std::{cmp::Reverse, collections::HashMap, ops::Range};
+
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+
+use super::{Database, Task};
+
+/// A period of time in which a task was worked on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkSession {
+    /// When the session started.
+    #[serde(with = "time::serde::rfc3339")]
+    pub start: OffsetDateTime,
+    /// When the session ended, or `None` if its timer is still running.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end: Option<OffsetDateTime>,
+}
+
+/// The time spent on tasks in a period of time. See [`Database::time_report`].
+#[derive(Debug, Clone, Default)]
+pub struct TimeReport<'a> {
+    /// The tasks that were worked on and the time spent on them, most time first.
+    pub tasks: Vec<(&'a Task, Duration)>,
+    /// The time spent on tasks with each tag, most time first. Time spent on a task with multiple
+    /// tags counts towards each of them.
+    pub tags: Vec<(&'a str, Duration)>,
+    /// The total time spent on all tasks.
+    pub total: Duration,
+}
+
+impl WorkSession {
+    /// Gets the length of this session. A running session lasts until `now`.
+    #[must_use]
+    pub fn duration(&self, now: OffsetDateTime) -> Duration {
+        (self.end.unwrap_or(now) - self.start).max(Duration::ZERO)
+    }
+
+    /// Gets how much of this session falls within the given period. A running session lasts until
+    /// `now`.
+    #[must_use]
+    pub fn duration_within(&self, period: &Range<OffsetDateTime>, now: OffsetDateTime) -> Duration {
+        let start = self.start.max(period.start);
+        let end = self.end.unwrap_or(now).min(period.end);
+        (end - start).max(Duration::ZERO)
+    }
+}
+
+impl Task {
+    /// Gets when this task was first started, if it has been.
+    #[must_use]
+    pub fn time_started(&self) -> Option<OffsetDateTime> {
+        self.sessions.first().map(|session| session.start)
+    }
+
+    /// Checks whether this task has been started, which is the case once it has a session.
+    #[must_use]
+    pub fn is_started(&self) -> bool {
+        !self.sessions.is_empty()
+    }
+
+    /// Checks whether the timer of this task is running.
+    #[must_use]
+    pub fn is_timer_running(&self) -> bool {
+        self.sessions
+            .last()
+            .is_some_and(|session| session.end.is_none())
+    }
+
+    /// Starts a new session at the given time. Returns `false` if the timer was already running.
+    pub fn start_timer(&mut self, now: OffsetDateTime) -> bool {
+        if self.is_timer_running() {
+            return false;
+        }
+
+        self.sessions.push(WorkSession {
+            start: now,
+            end: None,
+        });
+        true
+    }
+
+    /// Ends the running session at the given time. Returns `false` if the timer was not running.
+    pub fn stop_timer(&mut self, now: OffsetDateTime) -> bool {
+        match self.sessions.last_mut() {
+            Some(session) if session.end.is_none() => {
+                session.end = Some(now.max(session.start));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Gets the total time spent on this task. A running session lasts until `now`.
+    #[must_use]
+    pub fn time_spent(&self, now: OffsetDateTime) -> Duration {
+        self.sessions
+            .iter()
+            .map(|session| session.duration(now))
+            .sum()
+    }
+}
+
+impl Database {
+    /// Gets all tasks with a running timer.
+    pub fn get_running_timers(&self) -> impl Iterator<Item = &Task> {
+        self.get_all_tasks().filter(|task| task.is_timer_running())
+    }
+
+    /// Stops the timers of all tasks at the given time. Returns how many timers were running.
+    pub fn stop_all_timers(&mut self, now: OffsetDateTime) -> usize {
+        self.graph
+            .node_weights_mut()
+            .map(|task| task.stop_timer(now))
+            .filter(|&stopped| stopped)
+            .count()
+    }
+
+    /// Gets the time spent on tasks within the given period. Running sessions last until `now`.
+    #[must_use]
+    pub fn time_report(
+        &self,
+        period: Range<OffsetDateTime>,
+        now: OffsetDateTime,
+    ) -> TimeReport<'_> {
+        let mut report = TimeReport::default();
+        let mut tags = HashMap::<&str, Duration>::new();
+        for task in self.get_all_tasks() {
+            let spent: Duration = task
+                .sessions
+                .iter()
+                .map(|session| session.duration_within(&period, now))
+                .sum();
+            if spent.is_zero() {
+                continue;
+            }
+
+            for tag in &task.tags {
+                *tags.entry(tag).or_default() += spent;
+            }
+            report.tasks.push((task, spent));
+            report.total += spent;
+        }
+
+        report.tasks.sort_by_key(|(_, spent)| Reverse(*spent));
+        report.tags = tags.into_iter().collect();
+        report
+            .tags
+            .sort_by_key(|&(tag, spent)| (Reverse(spent), tag));
+        report
+    }
+}
+
+/// Formats a duration in hours and minutes, such as `1h 30m`. Unlike an
+/// [`Estimate`](super::estimate::Estimate), this does not use work days, since time can be tracked
+/// outside of working hours.
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.whole_minutes().max(0);
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month};
+
+    use super::*;
+
+    fn nine_am() -> OffsetDateTime {
+        let date = Date::from_calendar_date(2024, Month::March, 1).unwrap();
+        date.with_hms(9, 0, 0).unwrap().assume_utc()
+    }
+
+    #[test]
+    fn timers() {
+        let start = nine_am();
+        let mut task = Task::create_now("task".into());
+        assert!(!task.is_started());
+        assert!(!task.stop_timer(start));
+
+        // pausing and resuming keeps the original start time
+        assert!(task.start_timer(start));
+        assert!(!task.start_timer(start + Duration::minutes(5)));
+        assert!(task.stop_timer(start + Duration::hours(1)));
+        assert!(task.start_timer(start + Duration::hours(2)));
+        assert!(task.is_timer_running());
+        assert_eq!(task.time_started(), Some(start));
+
+        let now = start + Duration::minutes(150);
+        assert_eq!(format_duration(task.time_spent(now)), "1h 30m");
+    }
+
+    #[test]
+    fn time_report() {
+        let start = nine_am();
+        let mut db = Database::default();
+        for (title, tags, hours) in [("a", vec!["work"], 2), ("b", vec!["work", "home"], 1)] {
+            let mut task = Task::create_now(title.into());
+            task.tags = tags.into_iter().map(String::from).collect();
+            task.start_timer(start);
+            task.stop_timer(start + Duration::hours(hours));
+            db.add_task(task);
+        }
+        let mut running = Task::create_now("c".into());
+        running.start_timer(start + Duration::hours(8));
+        db.add_task(running);
+
+        // the period cuts off the last half hour of the running timer
+        let now = start + Duration::hours(9);
+        let period = start..start + Duration::minutes(8 * 60 + 30);
+        let report = db.time_report(period, now);
+        let titles = report.tasks.iter().map(|(t, _)| t.title.as_str());
+        assert_eq!(titles.collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(format_duration(report.tasks[2].1), "30m");
+        assert_eq!(
+            report.tags,
+            [("work", Duration::hours(3)), ("home", Duration::hours(1))]
+        );
+        assert_eq!(format_duration(report.total), "3h 30m");
+
+        assert_eq!(db.get_running_timers().count(), 1);
+        assert_eq!(db.stop_all_timers(now), 1);
+        assert_eq!(db.get_running_timers().count(), 0);
+    }
+}
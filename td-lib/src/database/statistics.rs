@@ -0,0 +1,262 @@
+//! Statistics about the tasks in a database, such as how many tasks are completed each week.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use serde::Serialize;
+use time::{Date, Duration};
+
+use super::{Database, Task};
+
+/// The status of a task, as shown to the user. See [`Database::task_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    /// The task can be worked on, but has not been started.
+    Open,
+    /// The task has not been started and has unfinished dependencies.
+    Blocked,
+    /// The task has been started, but is not completed.
+    Started,
+    /// The task is completed.
+    Done,
+}
+
+/// The amount of tasks with each status. See [`Database::status_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct StatusCounts {
+    /// The amount of [open](TaskStatus::Open) tasks.
+    pub open: usize,
+    /// The amount of [blocked](TaskStatus::Blocked) tasks.
+    pub blocked: usize,
+    /// The amount of [started](TaskStatus::Started) tasks.
+    pub started: usize,
+    /// The amount of [completed](TaskStatus::Done) tasks.
+    pub done: usize,
+}
+
+/// The amount of tasks that were created and completed in a week. See
+/// [`Database::weekly_throughput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct WeeklyThroughput {
+    /// The Monday this week starts on.
+    pub week_start: Date,
+    /// The amount of tasks created in this week.
+    pub created: usize,
+    /// The amount of tasks completed in this week.
+    pub completed: usize,
+}
+
+impl TaskStatus {
+    /// Gets the name of this status, such as `open`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Blocked => "blocked",
+            Self::Started => "started",
+            Self::Done => "done",
+        }
+    }
+}
+
+impl Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Database {
+    /// Gets the status of the given task. A started task counts as started even if it has
+    /// unfinished dependencies.
+    #[must_use]
+    pub fn task_status(&self, task: &Task) -> TaskStatus {
+        if task.time_completed.is_some() {
+            TaskStatus::Done
+        } else if task.is_started() {
+            TaskStatus::Started
+        } else if self.has_unfinished_dependencies(task.id()) {
+            TaskStatus::Blocked
+        } else {
+            TaskStatus::Open
+        }
+    }
+
+    /// Counts the tasks with each status.
+    #[must_use]
+    pub fn status_counts(&self) -> StatusCounts {
+        let mut counts = StatusCounts::default();
+        for task in self.get_all_tasks() {
+            *match self.task_status(task) {
+                TaskStatus::Open => &mut counts.open,
+                TaskStatus::Blocked => &mut counts.blocked,
+                TaskStatus::Started => &mut counts.started,
+                TaskStatus::Done => &mut counts.done,
+            } += 1;
+        }
+        counts
+    }
+
+    /// Counts the tasks created and completed in each of the last `weeks` weeks, oldest first. The
+    /// last week is the one containing `today`. Weeks start on Monday.
+    #[must_use]
+    pub fn weekly_throughput(&self, today: Date, weeks: usize) -> Vec<WeeklyThroughput> {
+        let current_week_start =
+            today - Duration::days(today.weekday().number_days_from_monday().into());
+        let mut throughput = (0..weeks)
+            .rev()
+            .filter_map(|weeks_ago| {
+                Some(WeeklyThroughput {
+                    week_start: current_week_start
+                        .checked_sub(Duration::weeks(i64::try_from(weeks_ago).ok()?))?,
+                    created: 0,
+                    completed: 0,
+                })
+            })
+            .collect::<Vec<_>>();
+        let Some(first_week_start) = throughput.first().map(|week| week.week_start) else {
+            return throughput;
+        };
+
+        let week_index = |date: Date| {
+            let days = (date - first_week_start).whole_days();
+            usize::try_from(days / 7).ok().filter(|_| days >= 0)
+        };
+        for task in self.get_all_tasks() {
+            if let Some(week) = week_index(task.time_created.date()) {
+                if let Some(week) = throughput.get_mut(week) {
+                    week.created += 1;
+                }
+            }
+            if let Some(week) = task.time_completed.and_then(|time| week_index(time.date())) {
+                if let Some(week) = throughput.get_mut(week) {
+                    week.completed += 1;
+                }
+            }
+        }
+        throughput
+    }
+
+    /// Gets the average time between creating and completing a task, over all completed tasks.
+    /// Returns `None` if no tasks are completed.
+    #[must_use]
+    pub fn average_lead_time(&self) -> Option<Duration> {
+        let lead_times = self
+            .get_all_tasks()
+            .filter_map(|task| Some(task.time_completed? - task.time_created))
+            .map(|lead_time| lead_time.max(Duration::ZERO))
+            .collect::<Vec<_>>();
+        let count = i32::try_from(lead_times.len()).ok().filter(|&c| c > 0)?;
+        Some(lead_times.into_iter().sum::<Duration>() / count)
+    }
+
+    /// Counts the unfinished tasks at the end of each of the given days, optionally only counting
+    /// tasks with the given tag. Tasks count from the day they were created until the day they
+    /// were completed.
+    #[must_use]
+    pub fn burndown(&self, tag: Option<&str>, days: RangeInclusive<Date>) -> Vec<(Date, usize)> {
+        let tasks = self
+            .get_all_tasks()
+            .filter(|task| tag.is_none_or(|tag| task.tags.iter().any(|t| t == tag)))
+            .map(|task| {
+                (
+                    task.time_created.date(),
+                    task.time_completed.map(|t| t.date()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut burndown = vec![];
+        let mut day = *days.start();
+        while day <= *days.end() {
+            let remaining = tasks
+                .iter()
+                .filter(|(created, completed)| {
+                    *created <= day && completed.is_none_or(|completed| completed > day)
+                })
+                .count();
+            burndown.push((day, remaining));
+
+            let Some(next_day) = day.next_day() else {
+                break;
+            };
+            day = next_day;
+        }
+        burndown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Month, OffsetDateTime};
+
+    use super::*;
+    use crate::database::test_utils::add_task;
+
+    fn date(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::March, day).unwrap()
+    }
+
+    fn noon(day: u8) -> OffsetDateTime {
+        date(day).with_hms(12, 0, 0).unwrap().assume_utc()
+    }
+
+    /// Creates a task with the `work` tag for every `(created, completed)` pair of days in March
+    /// 2024, which starts on a Friday.
+    fn create_database(tasks: &[(u8, Option<u8>)]) -> Database {
+        let mut db = Database::default();
+        for &(created, completed) in tasks {
+            add_task(&mut db, "task", |task| {
+                task.time_created = noon(created);
+                task.time_completed = completed.map(noon);
+                task.tags.push("work".into());
+            });
+        }
+        db
+    }
+
+    #[test]
+    fn status_counts() {
+        let mut db = create_database(&[(1, None), (1, None), (1, None), (1, Some(2))]);
+        let ids = db
+            .get_all_tasks()
+            .map(|t| t.id().clone())
+            .collect::<Vec<_>>();
+        db.add_dependency(&ids[0], &ids[1]).unwrap();
+        db[&ids[2]].start_timer(noon(3));
+
+        let expected = StatusCounts {
+            open: 1,
+            blocked: 1,
+            started: 1,
+            done: 1,
+        };
+        assert_eq!(db.status_counts(), expected);
+    }
+
+    #[test]
+    fn throughput_and_lead_time() {
+        let db = create_database(&[(1, Some(4)), (4, Some(5)), (5, None), (12, Some(12))]);
+
+        let weeks = db.weekly_throughput(date(12), 3);
+        let week_starts = weeks.iter().map(|w| w.week_start.day()).collect::<Vec<_>>();
+        assert_eq!(week_starts, [26, 4, 11]);
+        let counts = weeks.iter().map(|w| (w.created, w.completed));
+        assert_eq!(counts.collect::<Vec<_>>(), [(1, 0), (2, 2), (1, 1)]);
+
+        // 3 days, 1 day and 0 days
+        assert_eq!(db.average_lead_time(), Some(Duration::hours(32)));
+        assert_eq!(Database::default().average_lead_time(), None);
+    }
+
+    #[test]
+    fn burndown() {
+        let db = create_database(&[(1, Some(3)), (2, None)]);
+        let remaining = db.burndown(Some("work"), date(1)..=date(4));
+        let counts = remaining.iter().map(|&(_, count)| count);
+        assert_eq!(counts.collect::<Vec<_>>(), [1, 2, 1, 1]);
+        assert!(db
+            .burndown(Some("home"), date(1)..=date(4))
+            .iter()
+            .all(|&(_, c)| c == 0));
+    }
+}
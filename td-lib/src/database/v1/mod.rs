@@ -1,92 +1,28 @@
-//! The current version of the database, starting at v0.1.
+//! The first version of the database, used before v0.1. This is only kept around to migrate older
+//! database files to the current version.
 
-mod file_model;
-
-use std::collections::HashMap;
-
-use petgraph::stable_graph::{NodeIndex, StableDiGraph};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use time::OffsetDateTime;
 
-use self::file_model::*;
-
-/// The in-memory representation of the database
-#[derive(Debug, Clone, Default)]
-pub struct Database {
-    /// The graph of tasks in this database.
-    ///
-    /// This uses a StableDiGraph to keep a stable order, which means insertions and removals will
-    /// not cause large changes to the database file.
-    pub(crate) graph: StableDiGraph<Task, TaskDependency>,
-
-    /// A lookup cache
-    pub(crate) task_id_to_index: HashMap<TaskId, NodeIndex>,
+/// The database model as stored to disk.
+#[derive(Deserialize)]
+pub struct DatabaseDiskModel {
+    pub tasks: Vec<TaskDiskModel>,
 }
 
-/// A completable task.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Task {
-    /// A unique id for this task
-    pub(crate) id: TaskId,
-    /// A short description of this task.
+#[derive(Deserialize)]
+pub struct TaskDiskModel {
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    pub id: String,
     pub title: String,
-    /// When the task has been created.
+    #[serde(default)]
+    pub description: String,
     pub time_created: OffsetDateTime,
-    /// If the task has been started, this is when that happened.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
     pub time_started: Option<OffsetDateTime>,
-    /// If the task has been completed, this is when that happened.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
     pub time_completed: Option<OffsetDateTime>,
-    /// A list of tags for this task.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
     pub tags: Vec<String>,
 }
-
-/// A marker type to indicate that the relation between 2 tasks is a dependency.
-#[derive(Debug, Clone, Default)]
-pub struct TaskDependency;
-
-/// A task ID.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct TaskId(String);
-
-// -- end public structs --
-
-impl Serialize for Database {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let model: DatabaseDiskModel = self.clone().into();
-        model.serialize(serializer)
-    }
-}
-
-impl<'de> Deserialize<'de> for Database {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let model = DatabaseDiskModel::deserialize::<D>(deserializer)?;
-        Ok(model.into())
-    }
-}
-
-impl TaskId {
-    // TODO: take iterator of existing ids to ensure no collisions are generated
-    pub(crate) fn new() -> Self {
-        const SAFE_ALPHABET: [char; 56] = [
-            '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'j',
-            'k', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B',
-            'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U',
-            'V', 'W', 'X', 'Y', 'Z',
-        ];
-
-        Self(nanoid::nanoid!(8, &SAFE_ALPHABET))
-    }
-}
-
-impl super::DatabaseImpl for Database {
-    const VERSION: u8 = 1;
-}
@@ -0,0 +1,74 @@
+//! The second version of the database, which stores timestamps in the RFC 3339 format. This is only
+//! kept around to migrate older database files to the current version.
+
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+
+use super::v1;
+use crate::errors::DatabaseReadError;
+
+/// Upgrades the data of a v1 database file to v2.
+pub fn migrate_from_v1(data: serde_json::Value) -> Result<serde_json::Value, DatabaseReadError> {
+    let model: v1::DatabaseDiskModel = serde_json::from_value(data)?;
+    Ok(serde_json::to_value(DatabaseDiskModel::from(model))?)
+}
+
+/// The database model as stored to disk.
+#[derive(Deserialize, Serialize)]
+pub struct DatabaseDiskModel {
+    pub tasks: Vec<TaskDiskModel>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TaskDiskModel {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time_created: OffsetDateTime,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_started: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_completed: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Date>,
+    /// Kept as raw json, since only the current version parses recurrence rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl From<v1::DatabaseDiskModel> for DatabaseDiskModel {
+    fn from(value: v1::DatabaseDiskModel) -> Self {
+        let tasks = value
+            .tasks
+            .into_iter()
+            .map(|task| TaskDiskModel {
+                dependencies: task.dependencies,
+                id: task.id,
+                title: task.title,
+                description: task.description,
+                time_created: task.time_created,
+                time_started: task.time_started,
+                time_completed: task.time_completed,
+                due_date: None,
+                recurrence: None,
+                tags: task.tags,
+            })
+            .collect();
+
+        Self { tasks }
+    }
+}
@@ -0,0 +1,103 @@
+//! The third version of the database, which stores the priority of a task in its own field instead
+//! of as a `P:n` tag. This is only kept around to migrate older database files to the current
+//! version.
+
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+
+use super::v2;
+use crate::errors::DatabaseReadError;
+
+/// Upgrades the data of a v2 database file to v3.
+pub fn migrate_from_v2(data: serde_json::Value) -> Result<serde_json::Value, DatabaseReadError> {
+    let model: v2::DatabaseDiskModel = serde_json::from_value(data)?;
+    Ok(serde_json::to_value(DatabaseDiskModel::from(model))?)
+}
+
+/// The database model as stored to disk.
+#[derive(Deserialize, Serialize)]
+pub struct DatabaseDiskModel {
+    pub tasks: Vec<TaskDiskModel>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct TaskDiskModel {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub time_created: OffsetDateTime,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_started: Option<OffsetDateTime>,
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_completed: Option<OffsetDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Date>,
+    /// Kept as raw json, since only the current version parses recurrence rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<serde_json::Value>,
+    /// From 0 for the highest priority to 4 for the lowest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// An estimate such as `1d 4h`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl From<v2::DatabaseDiskModel> for DatabaseDiskModel {
+    fn from(value: v2::DatabaseDiskModel) -> Self {
+        let tasks = value
+            .tasks
+            .into_iter()
+            .map(|task| {
+                // priorities used to be stored as tags such as `P:1`. if a task has multiple, the
+                // highest one (with the lowest number) is kept. tags that are not a valid priority
+                // are left alone.
+                let mut priority = None::<u8>;
+                let mut tags = task.tags;
+                tags.retain(|tag| match parse_priority_tag(tag) {
+                    Some(tag_priority) => {
+                        priority = Some(priority.map_or(tag_priority, |p| p.min(tag_priority)));
+                        false
+                    }
+                    None => true,
+                });
+
+                TaskDiskModel {
+                    dependencies: task.dependencies,
+                    id: task.id,
+                    title: task.title,
+                    description: task.description,
+                    time_created: task.time_created,
+                    time_started: task.time_started,
+                    time_completed: task.time_completed,
+                    due_date: task.due_date,
+                    recurrence: task.recurrence,
+                    priority,
+                    estimate: None,
+                    tags,
+                }
+            })
+            .collect();
+
+        Self { tasks }
+    }
+}
+
+fn parse_priority_tag(tag: &str) -> Option<u8> {
+    let value = tag.strip_prefix("P:").or_else(|| tag.strip_prefix("p:"))?;
+    value.parse::<u8>().ok().filter(|priority| *priority <= 4)
+}
@@ -0,0 +1,160 @@
+use petgraph::stable_graph::StableDiGraph;
+use serde::{de::Error as _, Deserialize, Serialize};
+
+use super::*;
+use crate::{
+    database::{sessions::WorkSession, v3},
+    errors::DatabaseReadError,
+};
+
+/// Upgrades the data of a v3 database file to v4.
+pub fn migrate_from_v3(data: serde_json::Value) -> Result<serde_json::Value, DatabaseReadError> {
+    let model: v3::DatabaseDiskModel = serde_json::from_value(data)?;
+    Ok(serde_json::to_value(DatabaseDiskModel::try_from(model)?)?)
+}
+
+/// The database model as stored to disk.
+#[derive(Deserialize, Serialize)]
+pub struct DatabaseDiskModel {
+    tasks: Vec<TaskDiskModel>,
+}
+
+impl From<Database> for DatabaseDiskModel {
+    fn from(value: Database) -> Self {
+        let mut list = vec![];
+
+        // collect nodes
+        for node_idx in value.graph.node_indices() {
+            let node_weight = value.graph[node_idx].clone();
+            list.push((node_idx, TaskDiskModel::new(node_weight)));
+        }
+
+        // collect edges
+        for edge_idx in value.graph.edge_indices() {
+            let (start_index, end_index) = value
+                .graph
+                .edge_endpoints(edge_idx)
+                .expect("each edge should be connected");
+
+            let end_id = list
+                .iter()
+                .find_map(|x| (x.0 == end_index).then(|| x.1.task.id.clone()))
+                .expect("should be able to find end node");
+            let start_node = list
+                .iter_mut()
+                .find(|x| x.0 == start_index)
+                .expect("should be able to find start node");
+
+            start_node.1.dependencies.push(end_id);
+        }
+
+        Self {
+            tasks: list.into_iter().map(|x| x.1).collect(),
+        }
+    }
+}
+
+impl From<DatabaseDiskModel> for Database {
+    fn from(value: DatabaseDiskModel) -> Self {
+        let mut graph = StableDiGraph::new();
+        let mut id_index_map = HashMap::new();
+
+        // store nodes
+        for task in &value.tasks {
+            let id = task.task.id.clone();
+            let index = graph.add_node(task.task.clone());
+            id_index_map.insert(id, index);
+        }
+
+        // store edges
+        for task in &value.tasks {
+            let source_id = task.task.id.clone();
+            for target_id in task.dependencies.iter() {
+                let source_index = id_index_map[&source_id];
+                let target_index = id_index_map[target_id];
+
+                graph.add_edge(source_index, target_index, TaskDependency);
+            }
+        }
+
+        Self {
+            graph,
+            task_id_to_index: id_index_map,
+        }
+    }
+}
+
+impl TryFrom<v3::DatabaseDiskModel> for DatabaseDiskModel {
+    type Error = DatabaseReadError;
+
+    fn try_from(value: v3::DatabaseDiskModel) -> Result<Self, Self::Error> {
+        let tasks = value
+            .tasks
+            .into_iter()
+            .map(|task| {
+                // only the start time of a task used to be stored. a completed task is assumed to
+                // have been worked on until it was completed, but for an unfinished task it is not
+                // known how long it was worked on, so it gets a session without any time spent.
+                let sessions = task
+                    .time_started
+                    .map(|start| WorkSession {
+                        start,
+                        end: Some(task.time_completed.unwrap_or(start).max(start)),
+                    })
+                    .into_iter()
+                    .collect();
+
+                // invalid values fail the migration, like they would have when reading them as v3
+                let recurrence = task.recurrence.map(serde_json::from_value).transpose()?;
+                let priority = task
+                    .priority
+                    .map(Priority::try_from)
+                    .transpose()
+                    .map_err(serde_json::Error::custom)?;
+                let estimate = task
+                    .estimate
+                    .as_deref()
+                    .map(str::parse::<Estimate>)
+                    .transpose()
+                    .map_err(serde_json::Error::custom)?;
+
+                Ok(TaskDiskModel {
+                    dependencies: task.dependencies.into_iter().map(TaskId).collect(),
+                    task: Task {
+                        id: TaskId(task.id),
+                        title: task.title,
+                        description: task.description,
+                        time_created: task.time_created,
+                        sessions,
+                        time_completed: task.time_completed,
+                        due_date: task.due_date,
+                        recurrence,
+                        priority,
+                        estimate,
+                        tags: task.tags,
+                    },
+                })
+            })
+            .collect::<Result<_, DatabaseReadError>>()?;
+
+        Ok(Self { tasks })
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct TaskDiskModel {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<TaskId>,
+
+    #[serde(flatten)]
+    task: Task,
+}
+
+impl TaskDiskModel {
+    pub fn new(task: Task) -> Self {
+        Self {
+            task,
+            dependencies: vec![],
+        }
+    }
+}
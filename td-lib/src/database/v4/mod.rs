@@ -0,0 +1,205 @@
+//! The current version of the database. Compared to v3, a task stores every session it was worked
+//! on instead of only the time it was started.
+
+mod file_model;
+
+pub(super) use self::file_model::migrate_from_v3;
+
+use std::{collections::HashMap, convert::Infallible, fmt::Display, str::FromStr};
+
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+
+use self::file_model::*;
+use super::{estimate::Estimate, recurrence::Recurrence, sessions::WorkSession};
+use crate::errors::InvalidPriorityError;
+
+/// The in-memory representation of the database
+#[derive(Debug, Clone, Default)]
+pub struct Database {
+    /// The graph of tasks in this database.
+    ///
+    /// This uses a `StableDiGraph` to keep a stable order, which means insertions and removals will
+    /// not cause large changes to the database file.
+    pub(crate) graph: StableDiGraph<Task, TaskDependency>,
+
+    /// A lookup cache
+    pub(crate) task_id_to_index: HashMap<TaskId, NodeIndex>,
+}
+
+/// A completable task.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    /// A unique id for this task
+    pub(crate) id: TaskId,
+    /// A short description of this task.
+    pub title: String,
+    /// A longer, optional description of this task. May contain multiple lines.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// When the task has been created.
+    #[serde(with = "time::serde::rfc3339")]
+    pub time_created: OffsetDateTime,
+    /// The periods of time this task was worked on, oldest first. The task counts as started once
+    /// it has a session. See [`Task::start_timer`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions: Vec<WorkSession>,
+    /// If the task has been completed, this is when that happened.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_completed: Option<OffsetDateTime>,
+    /// The day by which this task should be completed, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<Date>,
+    /// If set, completing this task creates the next instance of it. See
+    /// [`Database::complete_task`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    /// How important this task is, if set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+    /// How long this task is expected to take, if estimated. See [`Database::remaining_estimate`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    /// A list of tags for this task.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A marker type to indicate that the relation between 2 tasks is a dependency.
+#[derive(Debug, Clone, Default)]
+pub struct TaskDependency;
+
+/// The priority of a task, from [`Priority::HIGHEST`] (`P0`) to [`Priority::LOWEST`] (`P4`).
+///
+/// A more important task has a higher priority but a lower number, so `P0` compares greater than
+/// `P1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct Priority(u8);
+
+/// A task ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskId(String);
+
+// -- end public structs --
+
+impl Serialize for Database {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let model: DatabaseDiskModel = self.clone().into();
+        model.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Database {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let model = DatabaseDiskModel::deserialize::<D>(deserializer)?;
+        Ok(model.into())
+    }
+}
+
+impl TaskId {
+    // TODO: take iterator of existing ids to ensure no collisions are generated
+    pub(crate) fn new() -> Self {
+        const SAFE_ALPHABET: [char; 56] = [
+            '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'j',
+            'k', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B',
+            'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R', 'S', 'T', 'U',
+            'V', 'W', 'X', 'Y', 'Z',
+        ];
+
+        Self(nanoid::nanoid!(8, &SAFE_ALPHABET))
+    }
+}
+
+impl Display for TaskId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for TaskId {
+    type Err = Infallible;
+
+    /// Parses a task id, such as one entered by a user. This does not check whether a task with
+    /// this id exists.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.trim().to_string()))
+    }
+}
+
+impl Priority {
+    /// The most important priority, `P0`.
+    pub const HIGHEST: Self = Self(0);
+    /// The least important priority, `P4`.
+    pub const LOWEST: Self = Self(4);
+
+    /// Gets the number of this priority, where 0 is the most important.
+    #[must_use]
+    pub fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Gets the next more important priority, or `None` if this is already the highest.
+    #[must_use]
+    pub fn raise(self) -> Option<Self> {
+        (self != Self::HIGHEST).then(|| Self(self.0 - 1))
+    }
+
+    /// Gets the next less important priority, or `None` if this is already the lowest.
+    #[must_use]
+    pub fn lower(self) -> Option<Self> {
+        (self != Self::LOWEST).then(|| Self(self.0 + 1))
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // a lower number means a higher priority
+        other.0.cmp(&self.0)
+    }
+}
+
+impl TryFrom<u8> for Priority {
+    type Error = InvalidPriorityError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value <= Self::LOWEST.0 {
+            Ok(Self(value))
+        } else {
+            Err(InvalidPriorityError(value))
+        }
+    }
+}
+
+impl From<Priority> for u8 {
+    fn from(value: Priority) -> Self {
+        value.0
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "P{}", self.0)
+    }
+}
+
+impl super::DatabaseImpl for Database {
+    const VERSION: u8 = 4;
+}
@@ -0,0 +1,110 @@
+//! Parsing of dates entered by the user.
+
+use time::{format_description, Date, Duration, Weekday};
+
+use crate::errors::DateParseError;
+
+/// Parses a date relative to `today`. Accepted formats are:
+///
+/// - an absolute date such as `2024-01-31`
+/// - `today`, `tomorrow` or `yesterday`
+/// - an offset in days or weeks such as `+3d`, `-1d` or `+2w`
+/// - a weekday such as `fri` or `friday`, which is the first such day after today
+pub fn parse_date(text: &str, today: Date) -> Result<Date, DateParseError> {
+    let error = || DateParseError(text.to_string());
+    let lowercase = text.trim().to_lowercase();
+
+    match lowercase.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return today.next_day().ok_or_else(error),
+        "yesterday" => return today.previous_day().ok_or_else(error),
+        _ => (),
+    }
+
+    if let Some(weekday) = parse_weekday(&lowercase) {
+        let mut date = today.next_day().ok_or_else(error)?;
+        while date.weekday() != weekday {
+            date = date.next_day().ok_or_else(error)?;
+        }
+        return Ok(date);
+    }
+
+    if let Some(offset) = lowercase.strip_prefix(['+', '-']) {
+        let (unit_index, unit) = offset.char_indices().next_back().ok_or_else(error)?;
+        let amount = offset[..unit_index].parse::<i64>().map_err(|_| error())?;
+        let unit_seconds = match unit {
+            'd' => 86_400,
+            'w' => 604_800,
+            _ => return Err(error()),
+        };
+        let mut seconds = amount.checked_mul(unit_seconds).ok_or_else(error)?;
+        if lowercase.starts_with('-') {
+            seconds = seconds.checked_neg().ok_or_else(error)?;
+        }
+        return today
+            .checked_add(Duration::seconds(seconds))
+            .ok_or_else(error);
+    }
+
+    let format =
+        format_description::parse("[year]-[month]-[day]").expect("valid hardcoded date format");
+    Date::parse(&lowercase, &format).map_err(|_| error())
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let weekdays = [
+        ("mon", Weekday::Monday),
+        ("tue", Weekday::Tuesday),
+        ("wed", Weekday::Wednesday),
+        ("thu", Weekday::Thursday),
+        ("fri", Weekday::Friday),
+        ("sat", Weekday::Saturday),
+        ("sun", Weekday::Sunday),
+    ];
+
+    weekdays.into_iter().find_map(|(short, weekday)| {
+        let full = weekday.to_string().to_lowercase();
+        (text == short || text == full).then_some(weekday)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+
+    #[test]
+    fn parse_dates() {
+        // a wednesday
+        let today = Date::from_calendar_date(2024, Month::January, 31).unwrap();
+        let date = |month, day| Date::from_calendar_date(2024, month, day).unwrap();
+
+        assert_eq!(parse_date("2024-03-01", today), Ok(date(Month::March, 1)));
+        assert_eq!(parse_date("Today", today), Ok(today));
+        assert_eq!(parse_date("tomorrow", today), Ok(date(Month::February, 1)));
+        assert_eq!(parse_date("+3d", today), Ok(date(Month::February, 3)));
+        assert_eq!(parse_date("-1d", today), Ok(date(Month::January, 30)));
+        assert_eq!(parse_date("+2w", today), Ok(date(Month::February, 14)));
+        assert_eq!(parse_date("fri", today), Ok(date(Month::February, 2)));
+        assert_eq!(parse_date("wednesday", today), Ok(date(Month::February, 7)));
+
+        let invalid_dates = [
+            "",
+            "+d",
+            "+3y",
+            "+3é",
+            "+999999999999999d",
+            "+99999999999999w",
+            "--9223372036854775808d",
+            "someday",
+            "2024-02-30",
+        ];
+        for invalid in invalid_dates {
+            assert_eq!(
+                parse_date(invalid, today),
+                Err(DateParseError(invalid.to_string()))
+            );
+        }
+    }
+}
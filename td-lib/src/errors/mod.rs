@@ -1,7 +1,11 @@
 //! Error types used by this crate
 
+use std::ops::Range;
+
 use thiserror::Error;
 
+use crate::database::TaskId;
+
 /// Errors that can occur when reading the task database.
 #[derive(Error, Debug)]
 pub enum DatabaseReadError {
@@ -17,3 +21,97 @@ pub enum DatabaseReadError {
     #[error("io error: {0}")]
     IoError(#[from] std::io::Error),
 }
+
+/// Errors that can occur when accessing or modifying tasks in the database.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// No task with the given id exists in the database.
+    #[error("task not found: {0}")]
+    NotFound(TaskId),
+
+    /// The requested change would introduce a dependency cycle.
+    #[error(transparent)]
+    DependencyCycle(#[from] DependencyCycleError),
+
+    /// A recurring task can not be reopened, because the next instance that was created when it
+    /// was completed has changed since.
+    #[error("the next instance {0} was changed since the task was completed")]
+    NextInstanceChanged(TaskId),
+}
+
+/// An error indicating that adding a dependency would introduce a cycle in the task graph.
+///
+/// Tasks in a cycle can never become actionable, since each of them (indirectly) depends on
+/// itself.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("dependency would create a cycle: {}", format_cycle(.0))]
+pub struct DependencyCycleError(pub Vec<TaskId>);
+
+fn format_cycle(cycle: &[TaskId]) -> String {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// An error that occured while parsing a filter query, see [`crate::database::query::Query`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{kind}")]
+pub struct QueryParseError {
+    /// What went wrong.
+    pub kind: QueryParseErrorKind,
+    /// The byte range in the query text that caused the error.
+    pub span: Range<usize>,
+}
+
+/// The reason a filter query could not be parsed.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryParseErrorKind {
+    /// A quoted string was not closed.
+    #[error("missing closing quote")]
+    UnterminatedQuote,
+
+    /// A filter such as `tag:` was used with an unknown name.
+    #[error("unknown filter `{0}`")]
+    UnknownFilter(String),
+
+    /// A filter was used without a value, such as `tag:`.
+    #[error("missing value for `{0}`")]
+    MissingValue(String),
+
+    /// A filter was given a value it does not understand.
+    #[error("invalid value `{value}` for `{filter}`, expected {expected}")]
+    InvalidValue {
+        /// The name of the filter.
+        filter: String,
+        /// The value that was given.
+        value: String,
+        /// A description of the values that are accepted.
+        expected: &'static str,
+    },
+}
+
+/// An error indicating that a date entered by the user could not be understood, see
+/// [`crate::dates::parse_date`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown date `{0}`, expected a date like 2024-01-31, today, tomorrow, +3d, +2w or fri")]
+pub struct DateParseError(pub String);
+
+/// An error indicating that a recurrence entered by the user could not be understood, see
+/// [`crate::database::recurrence::Recurrence`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown recurrence `{0}`, expected something like daily, weekly, monthly, every 3 days or weekly after completion")]
+pub struct RecurrenceParseError(pub String);
+
+/// An error indicating that a number is not a valid [`crate::database::Priority`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid priority {0}, expected a number from 0 (highest) to 4 (lowest)")]
+pub struct InvalidPriorityError(pub u8);
+
+/// An error indicating that an estimate entered by the user could not be understood, see
+/// [`crate::database::estimate::Estimate`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown estimate `{0}`, expected a duration like 30m, 2h, 3d, 1w or 1d 4h")]
+pub struct EstimateParseError(pub String);
@@ -8,6 +8,7 @@
 #![warn(missing_docs, clippy::doc_markdown, clippy::must_use_candidate)]
 
 pub mod database;
+pub mod dates;
 pub mod errors;
 
 pub use time;
@@ -0,0 +1,909 @@
+//! The non-interactive command-line interface, for use in scripts.
+
+use std::{error::Error, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use td_lib::{
+    database::{
+        analysis::TaskOrder,
+        database_file::{DatabaseFile, WriteOptions},
+        estimate::Estimate,
+        graphviz::DotOptions,
+        operations::{Operation, OperationLog},
+        query::Query,
+        recurrence::Recurrence,
+        sessions::{format_duration, TimeReport},
+        statistics::{StatusCounts, TaskStatus, WeeklyThroughput},
+        Database, Priority, Task, TaskId,
+    },
+    dates::parse_date,
+    errors::{DateParseError, TaskError},
+    time::{format_description::well_known::Rfc3339, Date, Duration, OffsetDateTime},
+};
+
+use crate::utils::{local_today, now};
+
+/// A graph-based todo app. Launches the TUI if no command is given.
+#[derive(Parser)]
+#[command(version)]
+pub struct Cli {
+    /// The database file to use. Defaults to `~/.td.json`.
+    #[arg(short, long, global = true)]
+    database: Option<PathBuf>,
+
+    /// The database file to use, for compatibility with older versions of td.
+    #[arg(hide = true)]
+    database_positional: Option<PathBuf>,
+
+    /// How many previous versions of the database to keep as timestamped backups when saving.
+    #[arg(long, global = true, default_value_t = 0)]
+    backups: usize,
+
+    /// How many undo steps to keep in a journal next to the database, so they can still be undone
+    /// after restarting the TUI.
+    #[arg(long, default_value_t = 0)]
+    pub undo_journal: usize,
+
+    /// Append every change to this file as a json line, so it can be inspected or replayed using
+    /// `td log`. Changes made in the TUI are appended when saving.
+    #[arg(long, global = true)]
+    log: Option<PathBuf>,
+
+    /// The author to record for changes in the log. Defaults to the current user.
+    #[arg(long, global = true)]
+    author: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    pub fn database_path(&self) -> PathBuf {
+        match self.database.as_ref().or(self.database_positional.as_ref()) {
+            Some(path) => path.clone(),
+            None => {
+                let home_dir = dirs::home_dir().expect("Failed to find home directory");
+                home_dir.join(".td.json")
+            }
+        }
+    }
+
+    pub fn write_options(&self) -> WriteOptions {
+        WriteOptions {
+            backup_count: self.backups,
+        }
+    }
+
+    pub fn operation_log(&self) -> Option<OperationLog> {
+        let author = self
+            .author
+            .clone()
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .unwrap_or_else(|| "unknown".into());
+        self.log
+            .as_ref()
+            .map(|path| OperationLog::new(path.clone(), author))
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a new task and print its id.
+    Add {
+        /// The title of the new task.
+        title: String,
+        /// A tag to add to the task. Can be given multiple times.
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// The id of a task that the new task depends on. Can be given multiple times.
+        #[arg(long)]
+        depends_on: Vec<TaskId>,
+        /// A longer description of the task.
+        #[arg(long)]
+        description: Option<String>,
+        /// When the task is due, such as 2024-01-31, tomorrow, +3d or fri.
+        #[arg(long, value_parser = parse_date_arg)]
+        due: Option<Date>,
+        /// How often the task repeats, such as daily, weekly, every 3 days or weekly after
+        /// completion. Completing the task creates the next instance.
+        #[arg(long)]
+        repeat: Option<Recurrence>,
+        /// The priority of the task, from 0 (highest) to 4 (lowest), such as 1 or P1.
+        #[arg(short, long, value_parser = parse_priority)]
+        priority: Option<Priority>,
+        /// How long the task is expected to take, such as 30m, 2h, 3d or 1d 4h.
+        #[arg(long)]
+        estimate: Option<Estimate>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// List tasks, newest first unless `--sort` is given.
+    List {
+        /// Only show tasks without unfinished dependencies.
+        #[arg(long)]
+        actionable: bool,
+        /// Also show completed tasks.
+        #[arg(short, long)]
+        all: bool,
+        /// Only show tasks with this tag.
+        #[arg(short, long)]
+        tag: Option<String>,
+        /// Only show tasks matching this filter query, such as `tag:backend -is:started`.
+        #[arg(short, long)]
+        query: Option<String>,
+        /// How to sort the tasks. Tasks that are equal in this order are sorted newest first.
+        #[arg(short, long, value_enum, default_value_t = SortArg::Created)]
+        sort: SortArg,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show the longest chain of unfinished tasks that depend on each other, in the order they
+    /// have to be completed.
+    CriticalPath {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show the details of a single task.
+    Show {
+        id: TaskId,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Mark a task as done. If the task repeats, the id of its next instance is printed.
+    Done { id: TaskId },
+    /// Start the timer of a task, which also marks it as started.
+    Start { id: TaskId },
+    /// Stop the timer of a task, or of all tasks if no id is given.
+    Stop { id: Option<TaskId> },
+    /// Show the time spent per task and per tag in a range of days.
+    Report {
+        /// The first day to include, such as 2024-01-01 or -7d. Defaults to 6 days ago.
+        #[arg(long, value_parser = parse_date_arg, allow_hyphen_values = true)]
+        from: Option<Date>,
+        /// The last day to include. Defaults to today.
+        #[arg(long, value_parser = parse_date_arg, allow_hyphen_values = true)]
+        to: Option<Date>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Show statistics about the tasks, such as how many were created and completed each week.
+    Stats {
+        /// The amount of weeks to show, including the current one.
+        #[arg(short, long, default_value_t = 8)]
+        weeks: usize,
+        /// Only count tasks with this tag for the burndown.
+        #[arg(short, long)]
+        tag: Option<String>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Add one or more tags to a task.
+    Tag {
+        id: TaskId,
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Export the task graph in the `GraphViz` DOT format.
+    Export {
+        /// Only export this task and the tasks it depends on.
+        #[arg(long)]
+        root: Option<TaskId>,
+        /// Also export completed tasks.
+        #[arg(short, long)]
+        all: bool,
+        /// The file to write to. Prints to stdout if not given.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Show the changes recorded in a log written using `--log`, oldest first.
+    Log {
+        /// The log file to read.
+        file: PathBuf,
+        /// Apply the logged changes to the database instead. Changes that were already applied are
+        /// skipped.
+        #[arg(long)]
+        replay: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+#[derive(Args)]
+pub struct OutputArgs {
+    /// Print output as json.
+    #[arg(long)]
+    json: bool,
+}
+
+/// The orders that tasks can be listed in, see [`TaskOrder`].
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortArg {
+    Created,
+    Due,
+    Priority,
+    EffectivePriority,
+    Unblocks,
+    CriticalPath,
+}
+
+impl From<SortArg> for TaskOrder {
+    fn from(value: SortArg) -> Self {
+        match value {
+            SortArg::Created => Self::Unchanged,
+            SortArg::Due => Self::DueDate,
+            SortArg::Priority => Self::Priority,
+            SortArg::EffectivePriority => Self::EffectivePriority,
+            SortArg::Unblocks => Self::UnblockedWork,
+            SortArg::CriticalPath => Self::CriticalPath,
+        }
+    }
+}
+
+/// A task as printed in json output, including its relations to other tasks.
+#[derive(Serialize)]
+struct TaskOutput<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    status: TaskStatus,
+    dependencies: Vec<&'a TaskId>,
+    dependents: Vec<&'a TaskId>,
+    /// The highest priority of this task and the tasks that depend on it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    effective_priority: Option<Priority>,
+    /// The amount of unfinished tasks that (indirectly) depend on this task.
+    unblocks: usize,
+}
+
+/// A time report as printed in json output. Durations are in whole minutes.
+#[derive(Serialize)]
+struct ReportOutput<'a> {
+    total_minutes: i64,
+    tasks: Vec<ReportTaskOutput<'a>>,
+    tags: Vec<ReportTagOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct ReportTaskOutput<'a> {
+    id: &'a TaskId,
+    title: &'a str,
+    minutes: i64,
+}
+
+#[derive(Serialize)]
+struct ReportTagOutput<'a> {
+    tag: &'a str,
+    minutes: i64,
+}
+
+impl<'a> ReportOutput<'a> {
+    fn new(report: &TimeReport<'a>) -> Self {
+        Self {
+            total_minutes: report.total.whole_minutes(),
+            tasks: report
+                .tasks
+                .iter()
+                .map(|(task, spent)| ReportTaskOutput {
+                    id: task.id(),
+                    title: &task.title,
+                    minutes: spent.whole_minutes(),
+                })
+                .collect(),
+            tags: report
+                .tags
+                .iter()
+                .map(|&(tag, spent)| ReportTagOutput {
+                    tag,
+                    minutes: spent.whole_minutes(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The statistics as printed in json output.
+#[derive(Serialize)]
+struct StatsOutput {
+    status: StatusCounts,
+    /// The average time between creating and completing a task, in days.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    average_lead_time_days: Option<f64>,
+    weeks: Vec<WeeklyThroughput>,
+    burndown: Vec<BurndownOutput>,
+}
+
+#[derive(Serialize)]
+struct BurndownOutput {
+    date: Date,
+    remaining: usize,
+}
+
+/// The database as loaded by a single command.
+struct CliContext {
+    path: PathBuf,
+    write_options: WriteOptions,
+    pending_upgrade: Option<u8>,
+    database: Database,
+    log: Option<OperationLog>,
+    /// Operations that were applied but not logged yet, since the database was not saved yet.
+    unlogged: Vec<Operation>,
+}
+
+impl CliContext {
+    fn load(
+        path: PathBuf,
+        write_options: WriteOptions,
+        log: Option<OperationLog>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let db_info = if path.exists() {
+            DatabaseFile::read(&path)?
+        } else {
+            DatabaseFile::default()
+        };
+        let pending_upgrade = db_info.requires_upgrade().then_some(db_info.version);
+
+        Ok(Self {
+            path,
+            write_options,
+            pending_upgrade,
+            database: db_info.try_into()?,
+            log,
+            unlogged: vec![],
+        })
+    }
+
+    fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(version) = self.pending_upgrade.take() {
+            let backup_path = DatabaseFile::create_backup(&self.path, version)?;
+            eprintln!("Upgraded database, the old version was kept at {backup_path:?}");
+        }
+
+        let db_info = DatabaseFile::from(&self.database);
+        db_info.write_with_options(&self.path, &self.write_options)?;
+
+        if let Some(log) = &self.log {
+            for operation in self.unlogged.drain(..) {
+                log.append(&operation, now())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies an operation to the database, which is logged when the database is saved. Returns
+    /// whether anything changed.
+    fn apply(&mut self, operation: Operation) -> Result<bool, TaskError> {
+        let changed = self.database.apply(&operation)?;
+        if changed {
+            self.unlogged.push(operation);
+        }
+        Ok(changed)
+    }
+
+    fn get_task(&self, id: &TaskId) -> Result<&Task, TaskError> {
+        self.database
+            .get(id)
+            .ok_or_else(|| TaskError::NotFound(id.clone()))
+    }
+
+    fn task_output<'a>(&'a self, task: &'a Task) -> TaskOutput<'a> {
+        TaskOutput {
+            task,
+            status: self.database.task_status(task),
+            dependencies: self
+                .database
+                .get_dependencies(task.id())
+                .map(|t| t.id())
+                .collect(),
+            dependents: self
+                .database
+                .get_inverse_dependencies(task.id())
+                .map(|t| t.id())
+                .collect(),
+            effective_priority: self.database.effective_priority(task.id()),
+            unblocks: self.database.get_transitive_dependents(task.id()).len(),
+        }
+    }
+
+    fn print_task_line(&self, task: &Task) {
+        println!(
+            "{}\t{}\t{}\t{}",
+            task.id(),
+            self.database.task_status(task).as_str(),
+            task.title,
+            task.tags.join(",")
+        );
+    }
+
+    fn print_task_details(&self, task: &Task) {
+        let output = self.task_output(task);
+        println!("id: {}", task.id());
+        println!("title: {}", task.title);
+        println!("status: {}", output.status);
+        println!("created: {}", format_time(task.time_created));
+        if let Some(time_started) = task.time_started() {
+            println!("started: {}", format_time(time_started));
+            let running = if task.is_timer_running() {
+                " (timer running)"
+            } else {
+                ""
+            };
+            println!(
+                "time spent: {}{running}",
+                format_duration(task.time_spent(now()))
+            );
+        }
+        if let Some(time_completed) = task.time_completed {
+            println!("completed: {}", format_time(time_completed));
+        }
+        if let Some(priority) = task.priority {
+            println!("priority: {priority}");
+        }
+        if output.effective_priority != task.priority {
+            if let Some(priority) = output.effective_priority {
+                println!("effective priority: {priority}");
+            }
+        }
+        if output.unblocks > 0 {
+            println!("unblocks: {}", output.unblocks);
+        }
+        if let Some(due_date) = task.due_date {
+            println!("due: {due_date}");
+        }
+        if let Some(recurrence) = task.recurrence {
+            println!("repeats: {recurrence}");
+        }
+        if let Some(estimate) = task.estimate {
+            println!("estimate: {estimate}");
+        }
+        let dependencies = self.database.get_transitive_dependencies(task.id());
+        if task.time_completed.is_none() && !dependencies.is_empty() {
+            let remaining = self.database.remaining_estimate(task.id());
+            println!("remaining with dependencies: {}", remaining.total);
+        }
+        if !task.tags.is_empty() {
+            println!("tags: {}", task.tags.join(","));
+        }
+        for dependency in output.dependencies {
+            println!("depends on: {dependency}");
+        }
+        for dependent in output.dependents {
+            println!("depended on by: {dependent}");
+        }
+        if !task.description.is_empty() {
+            println!();
+            println!("{}", task.description);
+        }
+    }
+}
+
+/// Runs a single command against the database at the given path.
+pub fn run(
+    command: Command,
+    path: PathBuf,
+    write_options: WriteOptions,
+    log: Option<OperationLog>,
+) -> Result<(), Box<dyn Error>> {
+    let mut ctx = CliContext::load(path, write_options, log)?;
+
+    match command {
+        Command::Add {
+            title,
+            tags,
+            depends_on,
+            description,
+            due,
+            repeat,
+            priority,
+            estimate,
+            output,
+        } => {
+            let mut task = Task::create_now(title);
+            task.description = description.unwrap_or_default();
+            task.due_date = due;
+            task.recurrence = repeat;
+            task.priority = priority;
+            task.estimate = estimate;
+            for tag in tags {
+                task.add_tag(tag);
+            }
+            let id = task.id().clone();
+            ctx.apply(Operation::AddTask { task })?;
+            for dependency in depends_on {
+                ctx.apply(Operation::AddDependency {
+                    from: id.clone(),
+                    to: dependency,
+                })?;
+            }
+            ctx.save()?;
+
+            let task = ctx.get_task(&id)?;
+            if output.json {
+                println!("{}", serde_json::to_string(&ctx.task_output(task))?);
+            } else {
+                println!("{id}");
+            }
+        }
+        Command::List {
+            actionable,
+            all,
+            tag,
+            query,
+            sort,
+            output,
+        } => {
+            let query = parse_query(query.as_deref().unwrap_or_default())?;
+            let mut tasks = ctx.database.get_all_tasks().collect::<Vec<_>>();
+            tasks.sort_by_key(|t| std::cmp::Reverse(t.time_created));
+            ctx.database.sort_tasks(&mut tasks, sort.into());
+            tasks.retain(|t| {
+                let status = ctx.database.task_status(t);
+                (all || !matches!(status, TaskStatus::Done))
+                    && (!actionable || !matches!(status, TaskStatus::Blocked | TaskStatus::Done))
+                    && tag.as_ref().is_none_or(|tag| t.tags.contains(tag))
+                    && query.matches(&ctx.database, t)
+            });
+
+            if output.json {
+                let tasks = tasks
+                    .into_iter()
+                    .map(|t| ctx.task_output(t))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&tasks)?);
+            } else {
+                for task in tasks {
+                    ctx.print_task_line(task);
+                }
+            }
+        }
+        Command::CriticalPath { output } => {
+            let path = ctx.database.critical_path()?;
+            if output.json {
+                let tasks = path
+                    .into_iter()
+                    .map(|t| ctx.task_output(t))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string(&tasks)?);
+            } else {
+                for task in path {
+                    ctx.print_task_line(task);
+                }
+            }
+        }
+        Command::Show { id, output } => {
+            let task = ctx.get_task(&id)?;
+            if output.json {
+                println!("{}", serde_json::to_string(&ctx.task_output(task))?);
+            } else {
+                ctx.print_task_details(task);
+            }
+        }
+        Command::Done { id } => {
+            let operation = Operation::mark_done(ctx.get_task(&id)?, now());
+            let next_id = operation.created_task().cloned();
+            if ctx.apply(operation)? {
+                ctx.save()?;
+                if let Some(next_id) = next_id {
+                    println!("{next_id}");
+                }
+            }
+        }
+        Command::Start { id } => {
+            if ctx.apply(Operation::StartTimer { id, started: now() })? {
+                ctx.save()?;
+            }
+        }
+        Command::Stop { id } => {
+            let ids = match id {
+                Some(id) => vec![id],
+                None => ctx
+                    .database
+                    .get_running_timers()
+                    .map(|task| task.id().clone())
+                    .collect(),
+            };
+            let time = now();
+            let mut stopped = false;
+            for id in ids {
+                stopped |= ctx.apply(Operation::StopTimer { id, stopped: time })?;
+            }
+            if stopped {
+                ctx.save()?;
+            }
+        }
+        Command::Report { from, to, output } => {
+            let to = to.unwrap_or_else(local_today);
+            let from = from.unwrap_or(to - Duration::days(6));
+            let offset = now().offset();
+            let period_end = to.next_day().unwrap_or(to).midnight().assume_offset(offset);
+            let report = ctx
+                .database
+                .time_report(from.midnight().assume_offset(offset)..period_end, now());
+
+            if output.json {
+                println!("{}", serde_json::to_string(&ReportOutput::new(&report))?);
+            } else {
+                println!("total\t{}", format_duration(report.total));
+                for (task, spent) in &report.tasks {
+                    let spent = format_duration(*spent);
+                    println!("task\t{}\t{spent}\t{}", task.id(), task.title);
+                }
+                for (tag, spent) in &report.tags {
+                    println!("tag\t{tag}\t{}", format_duration(*spent));
+                }
+            }
+        }
+        Command::Stats { weeks, tag, output } => {
+            let today = local_today();
+            let status = ctx.database.status_counts();
+            let average_lead_time_days = ctx
+                .database
+                .average_lead_time()
+                .map(|lead_time| lead_time.as_seconds_f64() / 86400.0);
+            let weeks = ctx.database.weekly_throughput(today, weeks);
+            let first_day = weeks.first().map_or(today, |week| week.week_start);
+            let burndown = ctx.database.burndown(tag.as_deref(), first_day..=today);
+
+            if output.json {
+                let stats = StatsOutput {
+                    status,
+                    average_lead_time_days,
+                    weeks,
+                    burndown: burndown
+                        .into_iter()
+                        .map(|(date, remaining)| BurndownOutput { date, remaining })
+                        .collect(),
+                };
+                println!("{}", serde_json::to_string(&stats)?);
+            } else {
+                println!("{}\t{}", TaskStatus::Open, status.open);
+                println!("{}\t{}", TaskStatus::Blocked, status.blocked);
+                println!("{}\t{}", TaskStatus::Started, status.started);
+                println!("{}\t{}", TaskStatus::Done, status.done);
+                if let Some(days) = average_lead_time_days {
+                    println!("lead time\t{days:.1} days");
+                }
+                for week in weeks {
+                    println!(
+                        "week\t{}\tcreated {}\tcompleted {}",
+                        week.week_start, week.created, week.completed
+                    );
+                }
+                for (date, remaining) in burndown {
+                    println!("burndown\t{date}\t{remaining}");
+                }
+            }
+        }
+        Command::Tag { id, tags } => {
+            let mut changed = false;
+            for tag in tags {
+                changed |= ctx.apply(Operation::AddTag {
+                    id: id.clone(),
+                    tag,
+                })?;
+            }
+            if changed {
+                ctx.save()?;
+            }
+        }
+        Command::Export { root, all, output } => {
+            let options = DotOptions {
+                include_completed: all,
+                root,
+            };
+            let dot = ctx.database.to_dot(&options)?;
+            match output {
+                Some(path) => std::fs::write(path, dot)?,
+                None => print!("{dot}"),
+            }
+        }
+        Command::Log {
+            file,
+            replay,
+            output,
+        } => {
+            let entries = OperationLog::read(&file)?;
+            if replay {
+                let mut applied = 0;
+                for (index, entry) in entries.iter().enumerate() {
+                    let changed = ctx
+                        .database
+                        .apply(&entry.operation)
+                        .map_err(|e| format!("entry {}: {e}", index + 1))?;
+                    applied += usize::from(changed);
+                }
+                if applied > 0 {
+                    ctx.save()?;
+                }
+                println!("applied {applied} of {} changes", entries.len());
+            } else if output.json {
+                println!("{}", serde_json::to_string(&entries)?);
+            } else {
+                for entry in entries {
+                    let time = format_time(entry.time);
+                    println!("{time}\t{}\t{}", entry.author, entry.operation);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a filter query, pointing out the invalid part of the query if it can't be parsed.
+fn parse_query(text: &str) -> Result<Query, Box<dyn Error>> {
+    Query::parse(text).map_err(|e| {
+        let offset = text[..e.span.start].chars().count();
+        let width = text[e.span.clone()].chars().count().max(1);
+        let marker = format!("{}{}", " ".repeat(offset), "^".repeat(width));
+        format!("{e}\n  {text}\n  {marker}").into()
+    })
+}
+
+fn parse_priority(text: &str) -> Result<Priority, String> {
+    let value = text
+        .strip_prefix(['P', 'p'])
+        .unwrap_or(text)
+        .parse::<u8>()
+        .map_err(|e| e.to_string())?;
+    Priority::try_from(value).map_err(|e| e.to_string())
+}
+
+fn parse_date_arg(text: &str) -> Result<Date, DateParseError> {
+    parse_date(text, local_today())
+}
+
+fn format_time(time: OffsetDateTime) -> String {
+    time.format(&Rfc3339)
+        .expect("task timestamps should be representable as rfc3339")
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    #[test]
+    fn verify_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parse_legacy_database_path() {
+        let cli = Cli::try_parse_from(["td", "db.json"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.database_path(), PathBuf::from("db.json"));
+
+        let cli = Cli::try_parse_from(["td", "list", "-d", "db.json", "--actionable"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::List {
+                actionable: true,
+                ..
+            })
+        ));
+        assert_eq!(cli.database_path(), PathBuf::from("db.json"));
+    }
+
+    #[test]
+    fn commands_modify_database() {
+        let path = std::env::temp_dir().join(format!("td-cli-test-{}.json", std::process::id()));
+        let run = |args: &[&str]| {
+            let cli = Cli::try_parse_from(["td"].iter().chain(args)).unwrap();
+            run(
+                cli.command.unwrap(),
+                path.clone(),
+                WriteOptions::default(),
+                None,
+            )
+        };
+
+        run(&["add", "task", "--tag", "a", "--due", "2024-03-01"]).unwrap();
+        let load = || CliContext::load(path.clone(), WriteOptions::default(), None).unwrap();
+        let id = load().database.get_all_tasks().next().unwrap().id().clone();
+
+        run(&["tag", &id.to_string(), "b"]).unwrap();
+        run(&["done", &id.to_string()]).unwrap();
+        let ctx = load();
+        let task = ctx.get_task(&id).unwrap();
+        assert_eq!(task.tags, ["a", "b"]);
+        assert!(task.time_completed.is_some());
+        assert_eq!(task.due_date.unwrap().to_string(), "2024-03-01");
+        assert!(Cli::try_parse_from(["td", "add", "task", "--due", "someday"]).is_err());
+
+        let chore = "add chore --due 2024-03-01 --repeat weekly -p P1 --estimate 1h30m";
+        run(&chore.split(' ').collect::<Vec<_>>()).unwrap();
+        let chore_id = load()
+            .database
+            .get_all_tasks()
+            .find(|t| t.title == "chore")
+            .unwrap()
+            .id()
+            .clone();
+        run(&["done", &chore_id.to_string()]).unwrap();
+        let ctx = load();
+        let next = ctx
+            .database
+            .get_all_tasks()
+            .find(|t| t.title == "chore" && t.time_completed.is_none())
+            .unwrap();
+        assert_eq!(next.recurrence.unwrap().to_string(), "weekly");
+        assert_eq!(next.priority.unwrap().value(), 1);
+        assert_eq!(next.estimate.unwrap().minutes(), 90);
+        assert!(Cli::try_parse_from(["td", "add", "task", "--priority", "5"]).is_err());
+        assert!(next.due_date.unwrap() > ctx.get_task(&chore_id).unwrap().due_date.unwrap());
+
+        assert!(run(&["start", "missing"]).is_err());
+        assert!(run(&["add", "task", "--depends-on", "missing"]).is_err());
+        assert_eq!(load().database.get_all_tasks().count(), 3);
+
+        let next_id = next.id().to_string();
+        run(&["start", &next_id]).unwrap();
+        assert!(load().get_task(next.id()).unwrap().is_timer_running());
+        run(&["stop"]).unwrap();
+        let stopped = load();
+        let stopped = stopped.get_task(next.id()).unwrap();
+        assert!(stopped.is_started() && !stopped.is_timer_running());
+        run(&["report", "--from", "-7d", "--json"]).unwrap();
+        run(&["stats", "--tag", "a", "--json"]).unwrap();
+
+        run(&["list", "--sort", "effective-priority", "--json"]).unwrap();
+        run(&["critical-path"]).unwrap();
+        assert!(Cli::try_parse_from(["td", "list", "--sort", "random"]).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn changes_are_logged_and_replayed() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("td-log-test-{}.json", std::process::id()));
+        let replay_path = dir.join(format!("td-log-test-{}-replay.json", std::process::id()));
+        let log_path = dir.join(format!("td-log-test-{}.jsonl", std::process::id()));
+        let run = |path: &PathBuf, args: &[&str]| {
+            let cli = Cli::try_parse_from(["td"].iter().chain(args)).unwrap();
+            let log = cli.operation_log();
+            run(
+                cli.command.unwrap(),
+                path.clone(),
+                WriteOptions::default(),
+                log,
+            )
+        };
+        let log = log_path.to_str().unwrap();
+
+        run(&path, &["add", "task", "--log", log, "--author", "alice"]).unwrap();
+        let id = CliContext::load(path.clone(), WriteOptions::default(), None)
+            .unwrap()
+            .database
+            .get_all_tasks()
+            .next()
+            .unwrap()
+            .id()
+            .to_string();
+        run(&path, &["tag", &id, "a", "b", "--log", log]).unwrap();
+        run(&path, &["tag", &id, "a", "--log", log]).unwrap();
+        run(&path, &["start", &id, "--log", log]).unwrap();
+        run(&path, &["stop", "--log", log]).unwrap();
+        run(&path, &["done", &id, "--log", log, "--author", "bob"]).unwrap();
+
+        let entries = OperationLog::read(&log_path).unwrap();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[0].author, "alice");
+        assert_eq!(entries[5].author, "bob");
+        run(&path, &["log", log]).unwrap();
+
+        run(&replay_path, &["log", log, "--replay"]).unwrap();
+        run(&replay_path, &["log", log, "--replay"]).unwrap();
+        let load = |path: &PathBuf| {
+            let ctx = CliContext::load(path.clone(), WriteOptions::default(), None).unwrap();
+            serde_json::to_value(&ctx.database).unwrap()
+        };
+        assert_eq!(load(&path), load(&replay_path));
+
+        for path in [path, replay_path, log_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! The undo journal, which keeps the recent undo history in a file next to the database. This
+//! allows undoing changes from before the last save after restarting.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use td_lib::{database::Database, errors::DatabaseReadError};
+use td_util::undo::{DatabaseDelta, Deltas, UndoJournal, UndoWrapper};
+
+#[derive(Serialize, Deserialize)]
+struct JournalFile {
+    /// A hash of the database file when the journal was written. The journal is ignored if the
+    /// database file was changed since, such as by a CLI command.
+    database_hash: u64,
+    history: UndoJournal<DatabaseDelta>,
+}
+
+/// Gets the path of the journal for a database file, such as `.td.json.undo` for `.td.json`.
+pub fn journal_path(database_path: &Path) -> PathBuf {
+    let mut file_name = database_path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".undo");
+    database_path.with_file_name(file_name)
+}
+
+/// Writes the current state and at most `max_steps` states before it to the journal. The database
+/// should have just been saved to `database_path`.
+pub fn write(
+    database_path: &Path,
+    database: &UndoWrapper<Database, Deltas>,
+    max_steps: usize,
+) -> Result<(), DatabaseReadError> {
+    let journal = JournalFile {
+        database_hash: hash(&std::fs::read(database_path)?),
+        history: database.to_journal(max_steps),
+    };
+    std::fs::write(journal_path(database_path), serde_json::to_vec(&journal)?)?;
+    Ok(())
+}
+
+/// Reads the journal of a database file. Returns `None` if there is no valid journal, or if it
+/// does not match the database file.
+pub fn read(database_path: &Path) -> Option<UndoJournal<DatabaseDelta>> {
+    let journal = std::fs::read(journal_path(database_path)).ok()?;
+    let journal: JournalFile = serde_json::from_slice(&journal).ok()?;
+    let database = std::fs::read(database_path).ok()?;
+    (journal.database_hash == hash(&database)).then_some(journal.history)
+}
+
+/// Hashes the contents of a file using 64-bit FNV-1a. Unlike the hashers in std, this gives the
+/// same result across Rust versions.
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -7,22 +7,51 @@ pub const KEYBIND_TASKPAGE_PANE_SETTINGS: &SimpleKeybind =
 pub const KEYBIND_TASKPAGE_PANE_TASKS: &SimpleKeybind =
     &SimpleKeybind::new(KeyCode::Left, "Select tasks pane");
 
-pub const KEYBIND_TASK_MARK_STARTED: &SimpleKeybind =
-    &SimpleKeybind::new(KeyCode::Char(' '), "Mark as started");
+pub const KEYBIND_TASK_TOGGLE_TIMER: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char(' '), "Start/stop timer");
+pub const KEYBIND_TASK_STOP_ALL_TIMERS: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('S'), "Stop all timers");
 pub const KEYBIND_TASK_MARK_DONE: &SimpleKeybind =
     &SimpleKeybind::new(KeyCode::Enter, "Mark as done");
 pub const KEYBIND_TASK_NEW: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('n'), "New task");
 pub const KEYBIND_TASK_DELETE: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('x'), "Delete");
 pub const KEYBIND_TASK_EDIT: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('e'), "Edit");
 pub const KEYBIND_TASK_ADD_TAG: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('t'), "Add tag");
+pub const KEYBIND_TASK_REMOVE_TAG: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('T'), "Remove tag");
+pub const KEYBIND_TASK_RENAME_TAG: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('R'), "Rename tag");
 pub const KEYBIND_TASK_ADD_DEPENDENCY: &SimpleKeybind =
     &SimpleKeybind::new(KeyCode::Char('d'), "Add dependency");
+pub const KEYBIND_TASK_REMOVE_DEPENDENCY: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('D'), "Remove dependency");
 pub const KEYBIND_TASK_RENAME: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('r'), "Rename");
+pub const KEYBIND_TASK_EDIT_DESCRIPTION: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('E'), "Edit description");
+pub const KEYBIND_TASK_SET_DUE_DATE: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('w'), "Set due date");
+pub const KEYBIND_TASK_SET_RECURRENCE: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('o'), "Set recurrence");
+pub const KEYBIND_TASK_SET_ESTIMATE: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('m'), "Set estimate");
+pub const KEYBIND_TASK_RAISE_PRIORITY: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('+'), "Raise priority");
+pub const KEYBIND_TASK_LOWER_PRIORITY: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('-'), "Lower priority");
 pub const KEYBIND_TASK_TOGGLE_SEARCH: &SimpleKeybind =
     &SimpleKeybind::new_mod(KeyCode::Char('s'), KeyModifiers::NONE, "Toggle search");
 pub const KEYBIND_TASK_CLOSE_SEARCH: &SimpleKeybind =
     &SimpleKeybind::new(KeyCode::Esc, "Close search");
 
+pub const KEYBIND_TREE_COLLAPSE_EXPAND: &LeftRightKeybind =
+    &LeftRightKeybind::new("Collapse/expand");
+
+pub const KEYBIND_STATS_BURNDOWN_TAG: &LeftRightKeybind =
+    &LeftRightKeybind::new("Change burndown tag");
+
+pub const KEYBIND_HISTORY_RESTORE: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Enter, "Restore state");
+
 pub const KEYBIND_TABS_NEXT: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Tab, "Next tab");
 pub const KEYBIND_TABS_PREV: &SimpleKeybind = &SimpleKeybind::new_hidden(KeyCode::BackTab);
 
@@ -36,11 +65,23 @@ pub const KEYBIND_MODAL_SUBMIT: &SimpleKeybind = &SimpleKeybind::new(KeyCode::En
 pub const KEYBIND_MODAL_SUBMITSELECT: &SimpleKeybind =
     &SimpleKeybind::new(KeyCode::Enter, "Select");
 pub const KEYBIND_MODAL_CANCEL: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Esc, "Cancel");
+pub const KEYBIND_MODAL_DISMISS: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Enter, "Dismiss");
 pub const KEYBIND_MODAL_LEFTRIGHT_OPTION: &LeftRightKeybind =
     &LeftRightKeybind::new("Choose option");
 
+pub const KEYBIND_ERROR_RETRY: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('r'), "Retry");
+pub const KEYBIND_ERROR_SAVE_AS: &SimpleKeybind =
+    &SimpleKeybind::new(KeyCode::Char('a'), "Save elsewhere");
+
+pub const KEYBIND_TEXTBOX_NEWLINE: &SimpleKeybind =
+    &SimpleKeybind::new_mod(KeyCode::Enter, KeyModifiers::ALT, "New line");
+
 pub const KEYBIND_SAVE: &SimpleKeybind =
     &SimpleKeybind::new_mod(KeyCode::Char('s'), KeyModifiers::CONTROL, "Save");
+pub const KEYBIND_EXPORT_GRAPH: &SimpleKeybind =
+    &SimpleKeybind::new_mod(KeyCode::Char('e'), KeyModifiers::CONTROL, "Export graph");
+pub const KEYBIND_RELOAD: &SimpleKeybind =
+    &SimpleKeybind::new_mod(KeyCode::Char('r'), KeyModifiers::CONTROL, "Reload");
 pub const KEYBIND_UNDO: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('u'), "Undo");
 pub const KEYBIND_REDO: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('U'), "Redo");
 pub const KEYBIND_QUIT: &SimpleKeybind = &SimpleKeybind::new(KeyCode::Char('q'), "Quit");
@@ -68,6 +109,7 @@ impl KeyCombo {
             (KeyCode::Left, Some(KeyModifiers::NONE) | None) => "←".into(),
             (KeyCode::Right, Some(KeyModifiers::NONE) | None) => "→".into(),
             (KeyCode::Enter, Some(KeyModifiers::NONE) | None) => "⏎".into(),
+            (KeyCode::Enter, Some(KeyModifiers::ALT)) => "M-⏎".into(),
             (KeyCode::Tab, Some(KeyModifiers::NONE) | None) => "⭾".into(),
             (KeyCode::Esc, Some(KeyModifiers::NONE) | None) => "⎋".into(),
 
@@ -110,6 +152,15 @@ impl SimpleKeybind {
             description: None,
         }
     }
+
+    /// Creates a copy of this keybind with a different description, such as one that depends on
+    /// the current state.
+    pub fn with_description(&self, description: String) -> Self {
+        Self {
+            key_combo: self.key_combo,
+            description: Some(Cow::Owned(description)),
+        }
+    }
 }
 
 impl Keybind for SimpleKeybind {
@@ -4,11 +4,16 @@
     clippy::cloned_instead_of_copied
 )]
 
+mod cli;
+mod journal;
 mod keybinds;
 mod ui;
 mod utils;
 
-use std::{error::Error, path::PathBuf, fs};
+use std::error::Error;
+
+use clap::Parser;
+use cli::Cli;
 
 use crossterm::{
     execute,
@@ -18,25 +23,28 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use ui::AppState;
 
 fn main() {
-    let path: PathBuf;
-    let args = std::env::args().skip(1).collect::<Vec<_>>();
-    if args.is_empty() {
-        let name = std::env::args()
-            .next()
-            .expect("There should always be 1 item");
-        let home_dir = dirs::home_dir().expect("Failed to find home directory");    
-        path = home_dir.join(".td.json");
-    }
-    else {
-        path = PathBuf::from(args[0].clone());
+    let cli = Cli::parse();
+    let path = cli.database_path();
+    let write_options = cli.write_options();
+    let operation_log = cli.operation_log();
+
+    if let Some(command) = cli.command {
+        if let Err(e) = cli::run(command, path, write_options, operation_log) {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+        return;
     }
-    let app = match AppState::create(path) {
+
+    let mut app = match AppState::create(path, cli.undo_journal) {
         Ok(app) => app,
         Err(e) => {
             println!("Error while loading database: {e}");
             return;
         }
     };
+    app.write_options = write_options;
+    app.operation_log = operation_log;
 
     if let Err(e) = run_app(app) {
         println!("Error while running app: {e}");
@@ -44,6 +52,14 @@ fn main() {
 }
 
 fn run_app(mut app: AppState) -> Result<(), Box<dyn Error>> {
+    // restore the terminal before printing panic messages, so they remain readable
+    let default_panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        _ = disable_raw_mode();
+        _ = execute!(std::io::stdout(), LeaveAlternateScreen);
+        default_panic_hook(info);
+    }));
+
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
 
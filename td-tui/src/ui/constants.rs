@@ -37,6 +37,14 @@ pub const FG_DIM: Style = Style {
     underline_color: None,
 };
 
+pub const FG_ACCENT: Style = Style {
+    fg: Some(ACCENT_COLOR),
+    bg: None,
+    add_modifier: Modifier::empty(),
+    sub_modifier: Modifier::empty(),
+    underline_color: None,
+};
+
 pub const FG_GREEN: Style = Style {
     fg: Some(Color::Green),
     bg: None,
@@ -109,6 +117,63 @@ pub const COMPLETED_TASK: Style = Style {
     underline_color: None,
 };
 
+/// The style for the due date of a task that should have been completed already
+pub const OVERDUE_TASK: Style = Style {
+    fg: Some(Color::Red),
+    bg: None,
+    add_modifier: Modifier::BOLD,
+    sub_modifier: Modifier::empty(),
+    underline_color: None,
+};
+
+/// The style for the due date of a task that should be completed soon
+pub const DUE_SOON_TASK: Style = Style {
+    fg: Some(Color::LightYellow),
+    bg: None,
+    add_modifier: Modifier::empty(),
+    sub_modifier: Modifier::empty(),
+    underline_color: None,
+};
+
+/// The styles for the priority of a task, indexed by its value. `P0` is the most important.
+pub const PRIORITY_STYLES: [Style; 5] = [
+    Style {
+        fg: Some(Color::LightRed),
+        bg: None,
+        add_modifier: Modifier::BOLD,
+        sub_modifier: Modifier::empty(),
+        underline_color: None,
+    },
+    Style {
+        fg: Some(Color::LightRed),
+        bg: None,
+        add_modifier: Modifier::empty(),
+        sub_modifier: Modifier::empty(),
+        underline_color: None,
+    },
+    Style {
+        fg: Some(Color::LightYellow),
+        bg: None,
+        add_modifier: Modifier::empty(),
+        sub_modifier: Modifier::empty(),
+        underline_color: None,
+    },
+    Style {
+        fg: Some(Color::LightBlue),
+        bg: None,
+        add_modifier: Modifier::empty(),
+        sub_modifier: Modifier::empty(),
+        underline_color: None,
+    },
+    Style {
+        fg: Some(Color::DarkGray),
+        bg: None,
+        add_modifier: Modifier::empty(),
+        sub_modifier: Modifier::empty(),
+        underline_color: None,
+    },
+];
+
 /// The style for unselected list items
 pub const LIST_STYLE: Style = Style {
     fg: Some(Color::Gray),
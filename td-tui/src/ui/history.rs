@@ -0,0 +1,181 @@
+use std::time::SystemTime;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+use td_lib::{database::sessions::format_duration, time::Duration};
+use td_util::undo::HistoryState;
+
+use super::{
+    constants::{BOLD, FG_ACCENT, FG_DIM, FG_GREEN, LIST_HIGHLIGHT_STYLE, LIST_STYLE},
+    AppState, Component, FrameLocalStorage,
+};
+use crate::keybinds::*;
+
+/// Lists every state in the undo history, newest first, and allows going back to any of them. This
+/// includes states on other branches, which were undone before making a different change.
+#[derive(Default)]
+pub struct HistoryPage {
+    selected_index: usize,
+}
+
+impl HistoryPage {
+    const SCROLL_PAGE_UP_DOWN: usize = 10;
+
+    fn get_rows(state: &AppState) -> Vec<HistoryState<'_>> {
+        let mut rows = state.database.history().collect::<Vec<_>>();
+        rows.reverse();
+        rows
+    }
+
+    fn row_to_line<'a>(
+        state: &AppState,
+        row: &HistoryState,
+        previous: Option<&HistoryState>,
+        now: SystemTime,
+    ) -> Line<'a> {
+        let is_current = row.id == state.database.current_id();
+        let is_undoable = state
+            .database
+            .ancestors(state.database.current_id())
+            .any(|id| id == row.id);
+
+        let mut spans = vec![
+            Span::styled(if is_current { "● " } else { "  " }, FG_ACCENT),
+            Span::styled(row.id.to_string(), if is_undoable { BOLD } else { FG_DIM }),
+            Span::raw("  "),
+            Span::raw(match row.description {
+                "" => "Initial state".to_string(),
+                description => description.to_string(),
+            }),
+            Span::raw("  "),
+            Span::styled(format_age(now, row.created), FG_DIM),
+        ];
+
+        // the state right below this one is usually its parent, unless this starts a new branch
+        if let Some(parent) = row.parent.filter(|&p| previous.is_none_or(|r| r.id != p)) {
+            spans.push(Span::styled(format!("  (branched from {parent})"), FG_DIM));
+        }
+        if state.database.clean_id() == Some(row.id) {
+            spans.push(Span::styled("  saved", FG_GREEN));
+        }
+
+        Line::from(spans)
+    }
+}
+
+/// Formats how long ago the given time was, such as `5m ago`.
+fn format_age(now: SystemTime, time: SystemTime) -> String {
+    let age = now.duration_since(time).unwrap_or_default();
+    match Duration::try_from(age) {
+        Ok(age) if age >= Duration::MINUTE => format!("{} ago", format_duration(age)),
+        _ => "just now".to_string(),
+    }
+}
+
+impl Component for HistoryPage {
+    fn pre_render(&self, global_state: &AppState, frame_storage: &mut FrameLocalStorage) {
+        let rows = Self::get_rows(global_state);
+        let selected_row = rows.get(self.selected_index.min(rows.len().saturating_sub(1)));
+
+        frame_storage.register_keybind(KEYBIND_CONTROLS_LIST_NAV_EXT, rows.len() >= 2);
+        frame_storage.register_keybind(
+            KEYBIND_HISTORY_RESTORE,
+            selected_row.is_some_and(|row| row.id != global_state.database.current_id()),
+        );
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        _frame_storage: &FrameLocalStorage,
+    ) {
+        let rows = Self::get_rows(state);
+        let now = SystemTime::now();
+
+        let list_items = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| ListItem::new(Self::row_to_line(state, row, rows.get(i + 1), now)))
+            .collect::<Vec<_>>();
+        let list = List::new(list_items)
+            .highlight_style(LIST_HIGHLIGHT_STYLE)
+            .style(LIST_STYLE);
+        let mut list_state = ListState::default();
+        list_state.select(
+            (!rows.is_empty()).then_some(self.selected_index.min(rows.len().saturating_sub(1))),
+        );
+        frame.render_stateful_widget(list, area, &mut list_state);
+    }
+
+    fn process_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        _frame_storage: &FrameLocalStorage,
+    ) -> bool {
+        let rows = Self::get_rows(state);
+        let last_index = rows.len().saturating_sub(1);
+        self.selected_index = self.selected_index.min(last_index);
+
+        if KEYBIND_HISTORY_RESTORE.is_match(key) {
+            if let Some(id) = rows.get(self.selected_index).map(|row| row.id) {
+                if state.go_to(id) {
+                    state.set_status_message(format!("Restored state {id}"));
+                }
+            }
+            true
+        } else if let Some(key) = KEYBIND_CONTROLS_LIST_NAV_EXT.get_match(key) {
+            self.selected_index = match key {
+                UpDownExtendedKey::Up => self.selected_index.saturating_sub(1),
+                UpDownExtendedKey::Down => (self.selected_index + 1).min(last_index),
+                UpDownExtendedKey::PageUp => self
+                    .selected_index
+                    .saturating_sub(Self::SCROLL_PAGE_UP_DOWN),
+                UpDownExtendedKey::PageDown => {
+                    (self.selected_index + Self::SCROLL_PAGE_UP_DOWN).min(last_index)
+                }
+                UpDownExtendedKey::Home => 0,
+                UpDownExtendedKey::End => last_index,
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+    use td_lib::database::Task;
+
+    use super::*;
+
+    #[test]
+    fn restore_state_on_other_branch() {
+        let mut state = AppState::default();
+        state.database = std::mem::take(&mut state.database).with_undo_tree();
+        for title in ["a", "b"] {
+            state
+                .database
+                .modify("Add task", |db| db.add_task(Task::create_now(title.into())));
+            state.database.undo();
+        }
+
+        // the newest state is listed first, followed by the other branch
+        let mut page = HistoryPage::default();
+        let frame_storage = FrameLocalStorage::default();
+        for code in [KeyCode::Down, KeyCode::Enter] {
+            page.process_input(KeyEvent::from(code), &mut state, &frame_storage);
+        }
+        let titles = state.database.get_all_tasks().map(|t| t.title.as_str());
+        assert_eq!(titles.collect::<Vec<_>>(), ["a"]);
+    }
+}
@@ -2,9 +2,10 @@ use ratatui::{
     text::{Line, Span},
     widgets::Paragraph,
 };
-use tui_input::Input;
+use tui_input::{Input, InputRequest};
 
 use crate::{
+    keybinds::*,
     ui::{
         constants::{TEXTBOX_STYLE, TEXTBOX_STYLE_BG},
         Component,
@@ -16,6 +17,7 @@ pub struct MultilineTextBoxComponent {
     input: Input,
     focused: bool,
     has_background: bool,
+    allow_newlines: bool,
 }
 
 impl MultilineTextBoxComponent {
@@ -33,6 +35,13 @@ impl MultilineTextBoxComponent {
         self
     }
 
+    /// Allows the user to insert explicit line breaks using [`KEYBIND_TEXTBOX_NEWLINE`].
+    #[must_use]
+    pub fn with_newlines(mut self, enabled: bool) -> Self {
+        self.allow_newlines = enabled;
+        self
+    }
+
     #[must_use]
     pub fn with_text(mut self, text: String) -> Self {
         self.input = Input::from(text);
@@ -46,16 +55,36 @@ impl MultilineTextBoxComponent {
 
     #[must_use]
     pub fn text_wrapped(&self, width: u16) -> Vec<String> {
-        wrap_text(self.input.value(), width)
+        // explicit line breaks always start a new line, so each line is wrapped separately
+        self.input
+            .value()
+            .split('\n')
+            .flat_map(|line| wrap_text(line, width))
+            .collect()
+    }
+
+    fn get_cursor_position(&self, width: u16) -> (u16, u16) {
+        let mut remaining = self.input.cursor();
+        let mut line_offset = 0;
+        for line in self.input.value().split('\n') {
+            let line_wrapped = wrap_text(line, width);
+            let line_len = line.chars().count();
+            if remaining <= line_len {
+                let (cursor_x, cursor_y) = Self::get_text_position(remaining, &line_wrapped);
+                return (cursor_x, line_offset + cursor_y);
+            }
+
+            // skip over the line and its line break
+            remaining -= line_len + 1;
+            line_offset += line_wrapped.len() as u16;
+        }
+        (0, line_offset)
     }
 
     fn get_text_position(naive_cursor_pos: usize, text_wrapped: &[String]) -> (u16, u16) {
         let (mut cursor_x, mut cursor_y) = (naive_cursor_pos, 0);
-        loop {
-            let Some(line) = text_wrapped.get(cursor_y) else {
-                break;
-            };
-            let line_len = line.len();
+        while let Some(line) = text_wrapped.get(cursor_y) {
+            let line_len = line.chars().count();
             if cursor_x <= line_len {
                 break;
             }
@@ -72,11 +101,22 @@ impl Default for MultilineTextBoxComponent {
             input: Default::default(),
             focused: true,
             has_background: true,
+            allow_newlines: false,
         }
     }
 }
 
 impl Component for MultilineTextBoxComponent {
+    fn pre_render(
+        &self,
+        _global_state: &crate::ui::AppState,
+        frame_storage: &mut crate::ui::FrameLocalStorage,
+    ) {
+        if self.focused && self.allow_newlines {
+            frame_storage.register_keybind(KEYBIND_TEXTBOX_NEWLINE, true);
+        }
+    }
+
     fn render(
         &self,
         frame: &mut ratatui::Frame,
@@ -97,7 +137,7 @@ impl Component for MultilineTextBoxComponent {
         frame.render_widget(paragraph, area);
 
         if self.focused {
-            let (cursor_x, cursor_y) = Self::get_text_position(self.input.cursor(), &text_wrapped);
+            let (cursor_x, cursor_y) = self.get_cursor_position(area.width);
 
             frame.set_cursor(area.x + cursor_x, area.y + cursor_y);
         }
@@ -114,7 +154,11 @@ impl Component for MultilineTextBoxComponent {
         }
 
         // TODO: handle up/down
-        // TODO: handle enter and ctrl+enter
+
+        if self.allow_newlines && KEYBIND_TEXTBOX_NEWLINE.is_match(key) {
+            self.input.handle(InputRequest::InsertChar('\n'));
+            return true;
+        }
 
         match process_textbox_input(&key) {
             Some(request) => {
@@ -1,4 +1,11 @@
-use std::{borrow::Cow, collections::HashSet, error::Error, io::Stdout, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fmt::Display,
+    io::Stdout,
+    path::PathBuf,
+};
 
 use crossterm::event::{self, Event, KeyEvent};
 use downcast_rs::{impl_downcast, Downcast};
@@ -6,45 +13,87 @@ use predicates::{
     prelude::{predicate, PredicateBooleanExt},
     BoxPredicate, PredicateBoxExt,
 };
-use ratatui::{backend::CrosstermBackend, layout::Rect, Frame, Terminal};
+use ratatui::{
+    backend::CrosstermBackend, layout::Rect, text::Span, widgets::Paragraph, Frame, Terminal,
+};
 use td_lib::{
-    database::{database_file::DatabaseFile, Database, Task, TaskId},
-    errors::DatabaseReadError,
+    database::{
+        analysis::TaskOrder,
+        database_file::{DatabaseFile, WriteOptions},
+        graphviz::DotOptions,
+        operations::{self, OperationLog},
+        Database, Task, TaskId, CURRENT_DATABASE_VERSION,
+    },
+    errors::{DatabaseReadError, TaskError},
 };
-use td_util::undo::UndoWrapper;
+use td_util::undo::{Deltas, Diff, StateId, UndoWrapper};
 
 use self::{
-    keybind_list::KeybindList, modal::ConfirmationModal, tab_layout::TabLayout, tasks::TaskPage,
+    constants::FG_ACCENT,
+    history::HistoryPage,
+    keybind_list::KeybindList,
+    modal::{ConfirmationModal, ErrorModal, TextInputModal},
+    statistics::StatisticsPage,
+    tab_layout::TabLayout,
+    tasks::{TaskPage, TaskTreePage},
 };
 use crate::{
+    journal,
     keybinds::*,
-    utils::{wrap_spans, MapPredicate, RectExt},
+    utils::{now, wrap_spans, MapPredicate, RectExt},
 };
 
 mod component_collection;
 mod constants;
 mod dirty_indicator;
+mod history;
 mod input;
 mod keybind_list;
 mod modal;
+mod statistics;
 mod tab_layout;
 mod tasks;
 
 #[cfg_attr(test, derive(Default))]
 pub struct AppState {
-    pub database: UndoWrapper<Database>,
+    pub database: UndoWrapper<Database, Deltas>,
     pub path: PathBuf,
+    /// If the database file on disk uses an older version, this is that version. The file will be
+    /// backed up and upgraded on the next save.
+    pub pending_upgrade: Option<u8>,
+    pub write_options: WriteOptions,
+    /// How many undo steps to keep in the journal next to the database file. The journal is not
+    /// used if this is 0.
+    pub undo_journal_steps: usize,
+    /// The log that changes are appended to when saving, if any.
+    pub operation_log: Option<OperationLog>,
+    /// Changes made since the last save, which are appended to the operation log on the next save.
+    unlogged_operations: Vec<operations::Operation>,
 
     should_exit: bool,
+    /// An error that has not been shown to the user yet.
+    pending_error: Option<AppError>,
+    /// A message about the last action, shown until the next key is pressed.
+    status_message: Option<String>,
 
     pub sort_oldest_first: bool,
+    /// How tasks are sorted. Tasks that are equal in this order are sorted by creation date.
+    pub sort_order: TaskOrder,
     pub filter_completed: bool,
     pub filter_unactionable: bool,
     pub filter_search: bool,
+    /// Tags that tasks must or must not have to be shown. Tags that are not in this map are not
+    /// used for filtering.
+    pub tag_filters: BTreeMap<String, TagFilter>,
 }
 
 impl AppState {
-    pub fn create(path: PathBuf) -> Result<Self, DatabaseReadError> {
+    /// How many previous states are kept in the undo history at most.
+    const MAX_UNDO_STEPS: usize = 1000;
+    /// Roughly how much memory the undo history may use at most.
+    const MAX_UNDO_BYTES: usize = 32 * 1024 * 1024;
+
+    pub fn create(path: PathBuf, undo_journal_steps: usize) -> Result<Self, DatabaseReadError> {
         let db_info = if !path.exists() {
             println!("The given database file ({path:?}) does not exist, creating a new one.");
 
@@ -55,17 +104,38 @@ impl AppState {
             DatabaseFile::read(&path)?
         };
 
-        let mut database: UndoWrapper<Database> = UndoWrapper::new(db_info.try_into()?);
-        database.mark_clean();
+        let pending_upgrade = db_info.requires_upgrade().then_some(db_info.version);
+
+        let mut database = UndoWrapper::<Database, Deltas>::with_storage(db_info.try_into()?)
+            .with_undo_tree()
+            .with_max_steps(Self::MAX_UNDO_STEPS)
+            .with_max_bytes(Self::MAX_UNDO_BYTES);
+        if pending_upgrade.is_none() {
+            if undo_journal_steps > 0 {
+                if let Some(history) = journal::read(&path) {
+                    database.restore_journal(history);
+                }
+            }
+            database.mark_clean();
+        }
 
         Ok(Self {
             database,
             path,
+            pending_upgrade,
+            write_options: WriteOptions::default(),
+            undo_journal_steps,
+            operation_log: None,
+            unlogged_operations: Vec::new(),
             should_exit: false,
+            pending_error: None,
+            status_message: None,
             sort_oldest_first: false,
+            sort_order: TaskOrder::default(),
             filter_completed: true,
             filter_unactionable: false,
             filter_search: false,
+            tag_filters: BTreeMap::new(),
         })
     }
 
@@ -73,7 +143,7 @@ impl AppState {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     ) -> Result<(), Box<dyn Error>> {
-        let mut root_component = LayoutRoot::new();
+        let mut root_component = LayoutRoot::new(self);
 
         'main_loop: loop {
             let mut frame_storage = FrameLocalStorage::default();
@@ -108,12 +178,228 @@ impl AppState {
         self.should_exit = true;
     }
 
-    /// Saves the database to disk and marks it as clean.
-    pub fn save(&mut self) {
-        // TODO: error handling. show popup on failure to save?
+    /// Reports an error to the user. It will be shown in a popup by [`LayoutRoot`].
+    pub fn report_error(&mut self, operation: Operation, error: impl Display) {
+        self.pending_error = Some(AppError {
+            operation,
+            message: error.to_string(),
+        });
+    }
+
+    /// Shows a message about the last action to the user, until the next key is pressed.
+    pub fn set_status_message(&mut self, message: String) {
+        self.status_message = Some(message);
+    }
+
+    /// Applies operations to the database as a single step with the given description, which can be
+    /// undone. Returns whether anything changed, no step is created otherwise. If any operation
+    /// fails, none of them are applied and the error is returned.
+    ///
+    /// The applied operations are appended to the operation log on the next save.
+    pub fn apply(
+        &mut self,
+        description: impl Into<String>,
+        operations: impl IntoIterator<Item = operations::Operation>,
+    ) -> Result<bool, TaskError> {
+        let result = self.database.try_modify(description, |db| {
+            let mut applied = Vec::new();
+            for operation in operations {
+                if db.apply(&operation)? {
+                    applied.push(operation);
+                }
+            }
+            // an error without a cause means nothing changed, so no undo step is created
+            if applied.is_empty() {
+                Err(None)
+            } else {
+                Ok(applied)
+            }
+        });
+
+        match result {
+            Ok(applied) => {
+                self.log_later(applied);
+                Ok(true)
+            }
+            Err(None) => Ok(false),
+            Err(Some(e)) => Err(e),
+        }
+    }
+
+    /// Undoes the last change, see [`UndoWrapper::undo`]. Returns whether anything changed.
+    pub fn undo(&mut self) -> bool {
+        self.change_state(UndoWrapper::undo)
+    }
+
+    /// Redoes the last undone change, see [`UndoWrapper::redo`]. Returns whether anything changed.
+    pub fn redo(&mut self) -> bool {
+        self.change_state(UndoWrapper::redo)
+    }
+
+    /// Moves to another state in the undo history, see [`UndoWrapper::go_to`]. Returns whether
+    /// anything changed.
+    pub fn go_to(&mut self, id: StateId) -> bool {
+        self.change_state(|db| db.go_to(id))
+    }
+
+    /// Moves to another state in the undo history using `change`, and logs the difference as a
+    /// single operation.
+    fn change_state(
+        &mut self,
+        change: impl FnOnce(&mut UndoWrapper<Database, Deltas>) -> bool,
+    ) -> bool {
+        let before = self
+            .operation_log
+            .is_some()
+            .then(|| (*self.database).clone());
+        if !change(&mut self.database) {
+            return false;
+        }
+        if let Some(before) = before {
+            let delta = Database::diff(&before, &self.database);
+            if !delta.is_empty() {
+                self.log_later([delta.into()]);
+            }
+        }
+        true
+    }
+
+    /// Queues operations to be appended to the operation log on the next save, if there is one.
+    fn log_later(&mut self, operations: impl IntoIterator<Item = operations::Operation>) {
+        if self.operation_log.is_some() {
+            self.unlogged_operations.extend(operations);
+        }
+    }
+
+    /// Saves the database to disk and marks it as clean. Returns whether saving succeeded. On
+    /// failure, the error is reported using [`Self::report_error`].
+    pub fn save(&mut self) -> bool {
+        match self.try_save() {
+            Ok(()) => {
+                self.write_undo_journal();
+                self.write_operation_log();
+                true
+            }
+            Err(e) => {
+                self.report_error(Operation::Save, e);
+                false
+            }
+        }
+    }
+
+    /// Saves the database to a different path, which will be used for all future saves if saving
+    /// succeeds. See [`Self::save`].
+    pub fn save_as(&mut self, path: PathBuf) -> bool {
+        let old_path = std::mem::replace(&mut self.path, path);
+
+        // the original file is left untouched, so it doesn't need to be backed up anymore
+        let old_pending_upgrade = self.pending_upgrade.take();
+
+        let success = self.save();
+        if !success {
+            self.path = old_path;
+            self.pending_upgrade = old_pending_upgrade;
+        }
+        success
+    }
+
+    /// Replaces the database with the one on disk, as a step that can be undone. Returns whether
+    /// reloading succeeded, see [`Self::save`].
+    pub fn reload(&mut self) -> bool {
+        match self.try_reload() {
+            Ok(()) => {
+                self.set_status_message(format!("Reloaded {}", self.path.display()));
+                true
+            }
+            Err(e) => {
+                self.report_error(Operation::Reload, e);
+                false
+            }
+        }
+    }
+
+    /// Exports the task graph to a `GraphViz` DOT file. Completed tasks are only included if they
+    /// are currently shown. Returns whether exporting succeeded, see [`Self::save`].
+    pub fn export_graph(&mut self, path: PathBuf) -> bool {
+        let options = DotOptions {
+            include_completed: !self.filter_completed,
+            root: None,
+        };
+        let result = self
+            .database
+            .to_dot(&options)
+            .map_err(|e| e.to_string())
+            .and_then(|dot| std::fs::write(&path, dot).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                self.report_error(Operation::Export(path), e);
+                false
+            }
+        }
+    }
+
+    fn try_reload(&mut self) -> Result<(), DatabaseReadError> {
+        let db_info = DatabaseFile::read(&self.path)?;
+        let pending_upgrade = db_info.requires_upgrade().then_some(db_info.version);
+        let database: Database = db_info.try_into()?;
+
+        self.database
+            .modify("Reload from disk", |db| *db = database);
+        // the unsaved changes are discarded, and the database on disk was already logged
+        self.unlogged_operations.clear();
+        self.pending_upgrade = pending_upgrade;
+        if pending_upgrade.is_none() {
+            self.database.mark_clean();
+        }
+        Ok(())
+    }
+
+    fn try_save(&mut self) -> Result<(), DatabaseReadError> {
+        if let Some(version) = self.pending_upgrade {
+            DatabaseFile::create_backup(&self.path, version)?;
+            self.pending_upgrade = None;
+        }
+
         let db_info: DatabaseFile = (&*self.database).into();
-        db_info.write(&self.path).unwrap();
+        db_info.write_with_options(&self.path, &self.write_options)?;
         self.database.mark_clean();
+        Ok(())
+    }
+
+    /// Writes the undo journal next to the database file, if it is enabled. The database itself
+    /// was already saved, so a failure is only shown as a status message instead of an error.
+    fn write_undo_journal(&mut self) {
+        if self.undo_journal_steps == 0 {
+            return;
+        }
+        if let Err(e) = journal::write(&self.path, &self.database, self.undo_journal_steps) {
+            self.set_status_message(format!("Saved, but failed to write the undo journal: {e}"));
+        }
+    }
+
+    /// Appends the changes made since the last save to the operation log, if there is one. Like
+    /// the undo journal, a failure is only shown as a status message.
+    fn write_operation_log(&mut self) {
+        let Some(log) = &self.operation_log else {
+            return;
+        };
+        let time = now();
+        let mut written = 0;
+        let mut error = None;
+        for operation in &self.unlogged_operations {
+            if let Err(e) = log.append(operation, time) {
+                error = Some(e);
+                break;
+            }
+            written += 1;
+        }
+        // operations that failed to be written are kept for the next save
+        self.unlogged_operations.drain(..written);
+        if let Some(e) = error {
+            self.set_status_message(format!("Saved, but failed to write the operation log: {e}"));
+        }
     }
 
     pub fn get_task_filter_predicate(&self) -> BoxPredicate<Task> {
@@ -129,11 +415,7 @@ impl AppState {
             let tasks_with_uncompleted_dependencies = self
                 .database
                 .get_all_tasks()
-                .filter(|t| {
-                    self.database
-                        .get_dependencies(t.id())
-                        .any(|dep| dep.time_completed.is_none())
-                })
+                .filter(|t| self.database.has_unfinished_dependencies(t.id()))
                 .map(|t| t.id().clone())
                 .collect::<HashSet<_>>();
 
@@ -146,10 +428,78 @@ impl AppState {
             predicate = predicate.and(has_uncompleted_dependencies.not()).boxed();
         }
 
+        if !self.tag_filters.is_empty() {
+            let tag_filters = self.tag_filters.clone();
+            let has_included_tags = tag_filters.values().any(|f| *f == TagFilter::Include);
+            predicate = predicate
+                .and(predicate::function(move |task: &Task| {
+                    let filters = task
+                        .tags
+                        .iter()
+                        .filter_map(|tag| tag_filters.get(tag).copied())
+                        .collect::<Vec<_>>();
+                    !filters.contains(&TagFilter::Exclude)
+                        && (!has_included_tags || filters.contains(&TagFilter::Include))
+                }))
+                .boxed();
+        }
+
         predicate
     }
 }
 
+/// Whether tasks with a certain tag should be shown in the task list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagFilter {
+    /// Only show tasks that have this tag, or any of the other included tags.
+    Include,
+    /// Hide tasks that have this tag.
+    Exclude,
+}
+
+/// An operation that can fail and be retried by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Save,
+    /// Reading the database from disk again.
+    Reload,
+    /// Exporting the task graph to the given path.
+    Export(PathBuf),
+}
+
+impl Operation {
+    fn retry(&self, state: &mut AppState) {
+        match self {
+            Self::Save => {
+                state.save();
+            }
+            Self::Reload => {
+                state.reload();
+            }
+            Self::Export(path) => {
+                state.export_graph(path.clone());
+            }
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Save => write!(f, "save"),
+            Self::Reload => write!(f, "reload"),
+            Self::Export(_) => write!(f, "export graph"),
+        }
+    }
+}
+
+/// An error that occured while performing an [`Operation`].
+#[derive(Debug, Clone)]
+pub struct AppError {
+    pub operation: Operation,
+    pub message: String,
+}
+
 /// Global storage for the current frame. Can be populated during [Component::pre_render] and read
 /// during [Component::render] and [Component::process_input].
 #[derive(Default)]
@@ -219,29 +569,193 @@ impl_downcast!(Component);
 struct LayoutRoot {
     tabs: TabLayout,
     save_unsaved_confirmation: ConfirmationModal,
+    upgrade_confirmation: ConfirmationModal,
+    error_modal: ErrorModal,
+    save_as_modal: TextInputModal,
+    export_modal: TextInputModal,
 }
 
 impl LayoutRoot {
-    fn new() -> Self {
+    fn new(state: &AppState) -> Self {
+        let mut upgrade_confirmation = ConfirmationModal::new(format!(
+            "This database will be upgraded from v{} to v{CURRENT_DATABASE_VERSION}. Older \
+            versions of td will no longer be able to read it, but a backup of the old file will \
+            be kept. Do you want to continue?",
+            state.pending_upgrade.unwrap_or_default(),
+        ))
+        .with_title("Upgrade database?".into());
+        if state.pending_upgrade.is_some() {
+            upgrade_confirmation.open(true);
+        }
+
         Self {
-            tabs: TabLayout::new([("Tasks", Box::new(TaskPage::new()) as Box<dyn Component>)]),
+            tabs: TabLayout::new([
+                ("Tasks", Box::new(TaskPage::new()) as Box<dyn Component>),
+                ("Dependency Tree", Box::new(TaskTreePage::new())),
+                ("Statistics", Box::<StatisticsPage>::default()),
+                ("History", Box::<HistoryPage>::default()),
+            ]),
             save_unsaved_confirmation: ConfirmationModal::new(
                 "There are unsaved changes. Do you want to save before quitting?".into(),
             )
             .with_title("Save before quitting?".into()),
+            upgrade_confirmation,
+            error_modal: ErrorModal::default(),
+            save_as_modal: TextInputModal::new("Save to path".into()),
+            export_modal: TextInputModal::new("Export graph to path".into()),
+        }
+    }
+
+    fn process_modal_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        frame_storage: &FrameLocalStorage,
+    ) -> bool {
+        if self.error_modal.is_open() {
+            if KEYBIND_ERROR_RETRY.is_match(key) {
+                if let Some(operation) = self.error_modal.close() {
+                    operation.retry(state);
+                }
+            } else if KEYBIND_ERROR_SAVE_AS.is_match(key)
+                && self.error_modal.operation() == Some(&Operation::Save)
+            {
+                self.error_modal.close();
+                self.save_as_modal
+                    .open_with_text(state.path.to_string_lossy().into_owned());
+            } else {
+                self.error_modal.process_input(key, state, frame_storage);
+            }
+            return true;
+        }
+
+        if self.save_as_modal.is_open() {
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(path) = self.save_as_modal.close() {
+                    state.save_as(PathBuf::from(path));
+                }
+                return true;
+            }
+            return self.save_as_modal.process_input(key, state, frame_storage);
+        }
+
+        if self.export_modal.is_open() {
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(path) = self.export_modal.close() {
+                    state.export_graph(PathBuf::from(path));
+                }
+                return true;
+            }
+            return self.export_modal.process_input(key, state, frame_storage);
+        }
+
+        if self.upgrade_confirmation.is_open() {
+            // declining the upgrade exits without touching the file on disk
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if self.upgrade_confirmation.close() {
+                    state.save();
+                } else {
+                    state.request_exit();
+                }
+            } else if KEYBIND_MODAL_CANCEL.is_match(key) {
+                self.upgrade_confirmation.close();
+                state.request_exit();
+            } else {
+                self.upgrade_confirmation
+                    .process_input(key, state, frame_storage);
+            }
+            return true;
+        }
+
+        if self
+            .save_unsaved_confirmation
+            .process_input(key, state, frame_storage)
+        {
+            return true;
+        }
+
+        if self.save_unsaved_confirmation.is_open() {
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                // if saving fails, stay open so the error can be shown
+                if !self.save_unsaved_confirmation.close() || state.save() {
+                    state.request_exit();
+                }
+                return true;
+            } else {
+                return false;
+            }
+        }
+
+        false
+    }
+
+    fn process_own_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        frame_storage: &FrameLocalStorage,
+    ) -> bool {
+        if self.tabs.process_input(key, state, frame_storage) {
+            return true;
+        }
+
+        if KEYBIND_SAVE.is_match(key) {
+            state.save();
+            true
+        } else if KEYBIND_RELOAD.is_match(key) {
+            state.reload();
+            true
+        } else if KEYBIND_EXPORT_GRAPH.is_match(key) {
+            let path = state.path.with_extension("dot");
+            self.export_modal
+                .open_with_text(path.to_string_lossy().into_owned());
+            true
+        } else if KEYBIND_UNDO.is_match(key) && state.database.undo_count() > 0 {
+            let description = state.database.undo_description().unwrap_or_default();
+            state.set_status_message(format!("Undone: {description}"));
+            state.undo();
+            true
+        } else if KEYBIND_REDO.is_match(key) && state.database.redo_count() > 0 {
+            let description = state.database.redo_description().unwrap_or_default();
+            state.set_status_message(format!("Redone: {description}"));
+            state.redo();
+            true
+        } else if KEYBIND_QUIT.is_match(key) || KEYBIND_QUIT_ALT.is_match(key) {
+            if state.database.is_dirty() {
+                self.save_unsaved_confirmation.open(true);
+            } else {
+                state.request_exit();
+            }
+            true
+        } else {
+            false
         }
     }
 }
 
 impl Component for LayoutRoot {
     fn pre_render(&self, state: &AppState, frame_storage: &mut FrameLocalStorage) {
+        self.error_modal.pre_render(state, frame_storage);
+        self.save_as_modal.pre_render(state, frame_storage);
+        self.export_modal.pre_render(state, frame_storage);
+        self.upgrade_confirmation.pre_render(state, frame_storage);
         self.save_unsaved_confirmation
             .pre_render(state, frame_storage);
         self.tabs.pre_render(state, frame_storage);
 
         frame_storage.register_keybind(KEYBIND_SAVE, state.database.is_dirty());
-        frame_storage.register_keybind(KEYBIND_UNDO, state.database.undo_count() > 0);
-        frame_storage.register_keybind(KEYBIND_REDO, state.database.redo_count() > 0);
+        frame_storage.register_keybind(KEYBIND_RELOAD, true);
+        frame_storage.register_keybind(KEYBIND_EXPORT_GRAPH, true);
+        let undo = match state.database.undo_description() {
+            Some(description) => KEYBIND_UNDO.with_description(format!("Undo: {description}")),
+            None => KEYBIND_UNDO.clone(),
+        };
+        let redo = match state.database.redo_description() {
+            Some(description) => KEYBIND_REDO.with_description(format!("Redo: {description}")),
+            None => KEYBIND_REDO.clone(),
+        };
+        frame_storage.register_keybind(&undo, state.database.undo_count() > 0);
+        frame_storage.register_keybind(&redo, state.database.redo_count() > 0);
         frame_storage.register_keybind(KEYBIND_QUIT, true);
         frame_storage.register_keybind(KEYBIND_QUIT_ALT, true);
     }
@@ -256,12 +770,24 @@ impl Component for LayoutRoot {
         let height = wrap_spans(KeybindList::get_spans(frame_storage), area.width).len() as u16;
 
         let (area_tabs, area_keybinds) = area.split_last_y(height);
+        let status_height = u16::from(state.status_message.is_some());
+        let (area_tabs, area_status) = area_tabs.split_last_y(status_height);
         self.tabs.render(frame, area_tabs, state, frame_storage);
 
+        if let Some(message) = &state.status_message {
+            let status = Paragraph::new(Span::styled(message.as_str(), FG_ACCENT));
+            frame.render_widget(status, area_status);
+        }
+
         KeybindList.render(frame, area_keybinds, state, frame_storage);
 
         self.save_unsaved_confirmation
             .render(frame, area, state, frame_storage);
+        self.upgrade_confirmation
+            .render(frame, area, state, frame_storage);
+        self.save_as_modal.render(frame, area, state, frame_storage);
+        self.export_modal.render(frame, area, state, frame_storage);
+        self.error_modal.render(frame, area, state, frame_storage);
     }
 
     fn process_input(
@@ -270,47 +796,210 @@ impl Component for LayoutRoot {
         state: &mut AppState,
         frame_storage: &FrameLocalStorage,
     ) -> bool {
-        if self
-            .save_unsaved_confirmation
-            .process_input(key, state, frame_storage)
-        {
-            return true;
-        }
+        state.status_message = None;
+        let handled = self.process_modal_input(key, state, frame_storage)
+            || self.process_own_input(key, state, frame_storage);
 
-        if self.save_unsaved_confirmation.is_open() {
-            if KEYBIND_MODAL_SUBMIT.is_match(key) {
-                if self.save_unsaved_confirmation.close() {
-                    state.save();
-                }
-                state.request_exit();
-                return true;
-            } else {
-                return false;
-            }
+        // show any errors that occured while processing this input
+        if let Some(error) = state.pending_error.take() {
+            self.error_modal.open(error);
         }
 
-        if self.tabs.process_input(key, state, frame_storage) {
-            return true;
+        handled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use predicates::Predicate;
+
+    use super::*;
+
+    #[test]
+    fn failed_save_is_reported() {
+        let mut state = AppState {
+            path: PathBuf::from("/nonexistent-td-directory/db.json"),
+            ..Default::default()
+        };
+        state.database.modify("Add task", |db| {
+            db.add_task(Task::create_now("task".into()));
+        });
+
+        assert!(!state.save());
+        assert!(state.database.is_dirty());
+        assert_eq!(state.database.get_all_tasks().count(), 1);
+
+        let error = state
+            .pending_error
+            .take()
+            .expect("error should be reported");
+        assert_eq!(error.operation, Operation::Save);
+    }
+
+    #[test]
+    fn undo_journal_survives_restart() {
+        let path =
+            std::env::temp_dir().join(format!("td-journal-test-{}.json", std::process::id()));
+        let mut state = AppState::create(path.clone(), 10).unwrap();
+        state.database.modify("Add task", |db| {
+            db.add_task(Task::create_now("task".into()));
+        });
+        assert!(state.save());
+
+        let mut state = AppState::create(path.clone(), 10).unwrap();
+        assert!(!state.database.is_dirty());
+        assert_eq!(state.database.undo_description(), Some("Add task"));
+        assert!(state.database.undo());
+        assert_eq!(state.database.get_all_tasks().count(), 0);
+
+        // the journal no longer applies once the database is changed elsewhere
+        let mut contents = std::fs::read(&path).unwrap();
+        contents.push(b'\n');
+        std::fs::write(&path, contents).unwrap();
+        let state = AppState::create(path.clone(), 10).unwrap();
+        assert_eq!(state.database.undo_description(), None);
+
+        _ = std::fs::remove_file(journal::journal_path(&path));
+        _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn undo_journal_failure_does_not_fail_save() {
+        let dir = std::env::temp_dir().join(format!("td-journal-fail-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("db.json");
+        // a directory in the way of the journal makes writing it fail
+        std::fs::create_dir_all(journal::journal_path(&path)).unwrap();
+
+        let mut state = AppState::create(path.clone(), 10).unwrap();
+        assert!(state.save_as(path.clone()));
+        assert!(state.pending_error.is_none());
+        assert!(state.status_message.is_some());
+        assert_eq!(state.path, path);
+
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn reload_can_be_undone() {
+        let path = std::env::temp_dir().join(format!("td-reload-test-{}.json", std::process::id()));
+        let mut state = AppState {
+            path: path.clone(),
+            ..Default::default()
+        };
+        assert!(!state.reload());
+        let error = state
+            .pending_error
+            .take()
+            .expect("error should be reported");
+        assert_eq!(error.operation, Operation::Reload);
+
+        assert!(state.save());
+        state.database.modify("Add task", |db| {
+            db.add_task(Task::create_now("task".into()));
+        });
+        assert!(state.reload());
+        assert_eq!(state.database.get_all_tasks().count(), 0);
+        assert!(!state.database.is_dirty());
+
+        assert!(state.database.undo());
+        assert_eq!(state.database.get_all_tasks().count(), 1);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn changes_are_logged_on_save() {
+        let dir = std::env::temp_dir().join(format!("td-log-tui-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log_path = dir.join("log.jsonl");
+        let mut state = AppState {
+            path: dir.join("db.json"),
+            operation_log: Some(OperationLog::new(log_path.clone(), "alice".into())),
+            ..Default::default()
+        };
+        let (a, b) = (Task::create_now("a".into()), Task::create_now("b".into()));
+        let (a_id, b_id) = (a.id().clone(), b.id().clone());
+
+        let add = [
+            operations::Operation::AddTask { task: a },
+            operations::Operation::AddTask { task: b },
+        ];
+        assert_eq!(state.apply("Add tasks", add), Ok(true));
+        let depend = operations::Operation::AddDependency {
+            from: a_id.clone(),
+            to: b_id.clone(),
+        };
+        assert_eq!(state.apply("Add dependency", [depend.clone()]), Ok(true));
+        assert_eq!(state.apply("Add dependency", [depend]), Ok(false));
+
+        // nothing is applied if one of the operations fails
+        let cycle = [
+            operations::Operation::Rename {
+                id: a_id.clone(),
+                title: "renamed".into(),
+            },
+            operations::Operation::AddDependency {
+                from: b_id,
+                to: a_id.clone(),
+            },
+        ];
+        assert!(state.apply("Add dependency", cycle).is_err());
+        assert_eq!(state.database[&a_id].title, "a");
+
+        assert!(state.undo());
+        assert!(!log_path.exists());
+        assert!(state.save());
+
+        let entries = OperationLog::read(&log_path).unwrap();
+        assert_eq!(entries.len(), 4);
+        let mut replayed = Database::default();
+        for entry in &entries {
+            replayed.apply(&entry.operation).unwrap();
         }
+        assert!(Database::diff(&replayed, &state.database).is_empty());
 
-        if KEYBIND_SAVE.is_match(key) {
-            state.save();
-            true
-        } else if KEYBIND_UNDO.is_match(key) && state.database.undo_count() > 0 {
-            state.database.undo();
-            true
-        } else if KEYBIND_REDO.is_match(key) && state.database.redo_count() > 0 {
-            state.database.redo();
-            true
-        } else if KEYBIND_QUIT.is_match(key) || KEYBIND_QUIT_ALT.is_match(key) {
-            if state.database.is_dirty() {
-                self.save_unsaved_confirmation.open(true);
-            } else {
-                state.request_exit();
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn filter_by_tags() {
+        let mut state = AppState::default();
+        for tags in [
+            &["backend"][..],
+            &["backend", "someday"],
+            &["frontend"],
+            &[],
+        ] {
+            let mut task = Task::create_now("task".into());
+            for tag in tags {
+                task.add_tag(tag.to_string());
             }
-            true
-        } else {
-            false
+            state.database.modify("Add task", |db| db.add_task(task));
         }
+        let count_visible = |state: &AppState| {
+            let predicate = state.get_task_filter_predicate();
+            state
+                .database
+                .get_all_tasks()
+                .filter(|t| predicate.eval(t))
+                .count()
+        };
+
+        assert_eq!(count_visible(&state), 4);
+
+        state
+            .tag_filters
+            .insert("someday".into(), TagFilter::Exclude);
+        assert_eq!(count_visible(&state), 3);
+
+        state
+            .tag_filters
+            .insert("backend".into(), TagFilter::Include);
+        assert_eq!(count_visible(&state), 1);
+
+        state
+            .tag_filters
+            .insert("frontend".into(), TagFilter::Include);
+        assert_eq!(count_visible(&state), 2);
     }
 }
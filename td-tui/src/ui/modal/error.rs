@@ -0,0 +1,90 @@
+use super::MessageModal;
+use crate::{
+    keybinds::*,
+    ui::{constants::FG_RED, AppError, Component, Operation},
+};
+
+/// A modal that shows an error that occured while performing an [`Operation`]. The parent is
+/// responsible for handling [`KEYBIND_ERROR_RETRY`] and [`KEYBIND_ERROR_SAVE_AS`].
+pub struct ErrorModal {
+    operation: Option<Operation>,
+    message: MessageModal,
+}
+
+impl Default for ErrorModal {
+    fn default() -> Self {
+        Self {
+            operation: None,
+            message: MessageModal::new(String::new()).with_border_style(FG_RED),
+        }
+    }
+}
+
+impl ErrorModal {
+    pub fn is_open(&self) -> bool {
+        self.operation.is_some()
+    }
+
+    pub fn open(&mut self, error: AppError) {
+        self.message
+            .set_title(format!("Failed to {}", error.operation));
+        self.message.open(error.message);
+        self.operation = Some(error.operation);
+    }
+
+    /// Closes the modal, returning the operation that failed.
+    pub fn close(&mut self) -> Option<Operation> {
+        self.message.close();
+        self.operation.take()
+    }
+
+    /// The operation that failed, if the modal is open.
+    pub fn operation(&self) -> Option<&Operation> {
+        self.operation.as_ref()
+    }
+}
+
+impl Component for ErrorModal {
+    fn pre_render(
+        &self,
+        global_state: &crate::ui::AppState,
+        frame_storage: &mut crate::ui::FrameLocalStorage,
+    ) {
+        if let Some(operation) = &self.operation {
+            frame_storage.register_keybind(KEYBIND_ERROR_RETRY, true);
+            if *operation == Operation::Save {
+                frame_storage.register_keybind(KEYBIND_ERROR_SAVE_AS, true);
+            }
+        }
+        self.message.pre_render(global_state, frame_storage);
+    }
+
+    fn render(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::layout::Rect,
+        state: &crate::ui::AppState,
+        frame_storage: &crate::ui::FrameLocalStorage,
+    ) {
+        self.message.render(frame, area, state, frame_storage);
+    }
+
+    fn process_input(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        _state: &mut crate::ui::AppState,
+        _frame_storage: &crate::ui::FrameLocalStorage,
+    ) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+
+        if KEYBIND_MODAL_DISMISS.is_match(key) || KEYBIND_MODAL_CANCEL.is_match(key) {
+            self.close();
+            return true;
+        }
+
+        // NOTE: returning false so that the parent gets a chance to check for other actions
+        false
+    }
+}
@@ -0,0 +1,116 @@
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::{
+    keybinds::*,
+    ui::{constants::MIN_MODAL_WIDTH, Component},
+    utils::{wrap_text, RectExt},
+};
+
+/// A modal that shows a message to the user until it is dismissed.
+pub struct MessageModal {
+    title: String,
+    text: Option<String>,
+    border_style: Style,
+}
+
+impl MessageModal {
+    pub fn new(title: String) -> Self {
+        Self {
+            title,
+            text: None,
+            border_style: Style::default(),
+        }
+    }
+
+    pub fn with_border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.text.is_some()
+    }
+
+    pub fn open(&mut self, text: String) {
+        self.text = Some(text);
+    }
+
+    pub fn close(&mut self) {
+        self.text = None;
+    }
+}
+
+impl Component for MessageModal {
+    fn pre_render(
+        &self,
+        _global_state: &crate::ui::AppState,
+        frame_storage: &mut crate::ui::FrameLocalStorage,
+    ) {
+        if self.is_open() {
+            frame_storage.register_keybind(KEYBIND_MODAL_DISMISS, true);
+            frame_storage.lock_keybinds();
+        }
+    }
+
+    fn render(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::layout::Rect,
+        _state: &crate::ui::AppState,
+        _frame_storage: &crate::ui::FrameLocalStorage,
+    ) {
+        let Some(text) = &self.text else {
+            return;
+        };
+
+        let block = Block::default()
+            .title(self.title.clone())
+            .borders(Borders::ALL)
+            .border_style(self.border_style);
+
+        let inner_width = MIN_MODAL_WIDTH
+            .max(self.title.len() as u16)
+            .max(area.width / 2)
+            .min(area.width.saturating_sub(2));
+
+        let wrapped_text = text
+            .lines()
+            .flat_map(|line| wrap_text(line, inner_width))
+            .map(|str| Line::from(Span::from(str)))
+            .collect::<Vec<_>>();
+        let inner_height = wrapped_text.len() as u16;
+
+        let block_area = area.center_rect(inner_width + 2, inner_height + 2);
+        let block_area_inner = block.inner(block_area);
+
+        frame.render_widget(Clear, block_area);
+        frame.render_widget(block, block_area);
+        frame.render_widget(Paragraph::new(wrapped_text), block_area_inner);
+    }
+
+    fn process_input(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        _state: &mut crate::ui::AppState,
+        _frame_storage: &crate::ui::FrameLocalStorage,
+    ) -> bool {
+        if !self.is_open() {
+            return false;
+        }
+
+        if KEYBIND_MODAL_DISMISS.is_match(key) || KEYBIND_MODAL_CANCEL.is_match(key) {
+            self.close();
+        }
+
+        // the message blocks all other input until it is dismissed
+        true
+    }
+}
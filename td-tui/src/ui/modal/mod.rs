@@ -1,9 +1,17 @@
 mod confirmation;
+mod error;
 mod keybind_select;
 mod list_search;
+mod message;
+mod parsed_input;
+mod text_area;
 mod text_input;
 
 pub use confirmation::ConfirmationModal;
+pub use error::ErrorModal;
 pub use keybind_select::KeybindSelectModal;
 pub use list_search::ListSearchModal;
+pub use message::MessageModal;
+pub use parsed_input::ParsedInputModal;
+pub use text_area::TextAreaModal;
 pub use text_input::TextInputModal;
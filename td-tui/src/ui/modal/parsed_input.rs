@@ -0,0 +1,138 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    keybinds::*,
+    ui::{
+        constants::{FG_DIM, FG_RED},
+        input::TextBoxComponent,
+        AppState, Component,
+    },
+    utils::{wrap_text, RectExt},
+};
+
+/// A modal to enter a value that is parsed while typing, such as a date. The parsed value is
+/// described below the input, or the reason it is invalid if it can't be parsed.
+pub struct ParsedInputModal<T> {
+    title: String,
+    input: Option<TextBoxComponent>,
+    parse: fn(&str) -> Result<T, String>,
+    describe: fn(&T) -> String,
+}
+
+impl<T> ParsedInputModal<T> {
+    const WIDTH: u16 = 40;
+
+    pub fn new(
+        title: String,
+        parse: fn(&str) -> Result<T, String>,
+        describe: fn(&T) -> String,
+    ) -> Self {
+        Self {
+            title,
+            input: None,
+            parse,
+            describe,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn open_with_text(&mut self, text: String) {
+        self.input = Some(TextBoxComponent::new_focused().with_text(text));
+    }
+
+    /// Gets the currently entered value, or `None` if the modal is not open.
+    pub fn value(&self) -> Option<Result<T, String>> {
+        Some((self.parse)(self.input.as_ref()?.text()))
+    }
+
+    pub fn close(&mut self) {
+        self.input = None;
+    }
+
+    fn preview_lines(&self) -> Vec<String> {
+        match self.value() {
+            Some(Ok(value)) => vec![format!("→ {}", (self.describe)(&value))],
+            Some(Err(e)) => wrap_text(&e, Self::WIDTH),
+            None => vec![],
+        }
+    }
+}
+
+impl<T: 'static> Component for ParsedInputModal<T> {
+    fn pre_render(
+        &self,
+        global_state: &AppState,
+        frame_storage: &mut crate::ui::FrameLocalStorage,
+    ) {
+        if let Some(input) = &self.input {
+            input.pre_render(global_state, frame_storage);
+
+            frame_storage
+                .register_keybind(KEYBIND_MODAL_SUBMIT, matches!(self.value(), Some(Ok(_))));
+            frame_storage.register_keybind(KEYBIND_MODAL_CANCEL, true);
+            frame_storage.lock_keybinds();
+        }
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        frame_storage: &crate::ui::FrameLocalStorage,
+    ) {
+        let Some(textbox) = &self.input else {
+            return;
+        };
+
+        let block = Block::default()
+            .title(self.title.clone())
+            .borders(Borders::ALL);
+
+        // put the block in the center of the area, with the preview below the input
+        let preview_lines = self.preview_lines();
+        let block_area = area.center_rect(Self::WIDTH + 2, preview_lines.len() as u16 + 3);
+        let block_area_inner = block.inner(block_area);
+        let (input_area, preview_area) = block_area_inner.split_y(1);
+
+        frame.render_widget(Clear, block_area);
+        frame.render_widget(block, block_area);
+        textbox.render(frame, input_area, state, frame_storage);
+
+        let preview_style = match self.value() {
+            Some(Err(_)) => FG_RED,
+            _ => FG_DIM,
+        };
+        frame.render_widget(
+            Paragraph::new(preview_lines.join("\n")).style(preview_style),
+            preview_area,
+        );
+    }
+
+    fn process_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        frame_storage: &crate::ui::FrameLocalStorage,
+    ) -> bool {
+        // always close with Esc
+        if self.is_open() && KEYBIND_MODAL_CANCEL.is_match(key) {
+            self.close();
+            return true;
+        }
+
+        let Some(input) = &mut self.input else {
+            return false;
+        };
+
+        input.process_input(key, state, frame_storage)
+    }
+}
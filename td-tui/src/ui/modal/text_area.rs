@@ -0,0 +1,113 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Borders, Clear},
+    Frame,
+};
+
+use crate::{
+    keybinds::*,
+    ui::{constants::MIN_MODAL_WIDTH, input::MultilineTextBoxComponent, AppState, Component},
+    utils::RectExt,
+};
+
+/// Like [`super::TextInputModal`], but larger and allowing explicit line breaks. Meant for longer
+/// pieces of text.
+pub struct TextAreaModal {
+    title: String,
+    input: Option<MultilineTextBoxComponent>,
+}
+
+impl TextAreaModal {
+    const MIN_HEIGHT: u16 = 5;
+
+    pub fn new(title: String) -> Self {
+        Self { title, input: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn open_with_text(&mut self, input: String) {
+        self.input = Some(
+            MultilineTextBoxComponent::new_focused()
+                .with_background(false)
+                .with_newlines(true)
+                .with_text(input),
+        );
+    }
+
+    pub fn close(&mut self) -> Option<String> {
+        self.input.take().map(|input| input.text().to_string())
+    }
+}
+
+impl Component for TextAreaModal {
+    fn pre_render(
+        &self,
+        global_state: &AppState,
+        frame_storage: &mut crate::ui::FrameLocalStorage,
+    ) {
+        if let Some(input) = &self.input {
+            input.pre_render(global_state, frame_storage);
+
+            frame_storage.register_keybind(KEYBIND_MODAL_SUBMIT, true);
+            frame_storage.register_keybind(KEYBIND_MODAL_CANCEL, true);
+            frame_storage.lock_keybinds();
+        }
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        frame_storage: &crate::ui::FrameLocalStorage,
+    ) {
+        let Some(textbox) = &self.input else {
+            return;
+        };
+
+        let block = Block::default()
+            .title(self.title.clone())
+            .borders(Borders::ALL);
+
+        // take up half the screen width, growing vertically as more text is entered
+        let max_width = area.width.saturating_sub(2);
+        let max_height = area.height.saturating_sub(2);
+        let block_width = MIN_MODAL_WIDTH
+            .max(self.title.len() as u16)
+            .max(area.width / 2)
+            .min(max_width);
+        let block_height = (textbox.text_wrapped(block_width).len() as u16)
+            .max(Self::MIN_HEIGHT)
+            .min(max_height);
+
+        let block_area = area.center_rect(block_width + 2, block_height + 2);
+        let block_area_inner = block.inner(block_area);
+
+        frame.render_widget(Clear, block_area);
+        frame.render_widget(block, block_area);
+        textbox.render(frame, block_area_inner, state, frame_storage);
+    }
+
+    fn process_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        frame_storage: &crate::ui::FrameLocalStorage,
+    ) -> bool {
+        // always close with Esc
+        if self.is_open() && KEYBIND_MODAL_CANCEL.is_match(key) {
+            self.close();
+            return true;
+        }
+
+        let Some(input) = &mut self.input else {
+            return false;
+        };
+
+        input.process_input(key, state, frame_storage)
+    }
+}
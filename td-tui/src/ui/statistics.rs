@@ -0,0 +1,245 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, GraphType, Paragraph, Sparkline},
+    Frame,
+};
+
+use super::{
+    constants::{BOLD, FG_ACCENT, FG_DIM, FG_GREEN, FG_WHITE},
+    AppState, Component, FrameLocalStorage,
+};
+use crate::{keybinds::*, utils::local_today};
+
+/// Shows statistics about the database, such as how many tasks are created and completed each
+/// week and a burndown of the unfinished tasks.
+#[derive(Default)]
+pub struct StatisticsPage {
+    /// The tag to show the burndown for. 0 means all tasks, otherwise it is the index in the sorted
+    /// list of tags plus 1.
+    tag_index: usize,
+}
+
+impl StatisticsPage {
+    /// The amount of weeks to show in the charts, including the current one.
+    const WEEKS: usize = 12;
+
+    /// Gets the tag to show the burndown for, or `None` to include all tasks.
+    fn selected_tag<'a>(&self, state: &'a AppState) -> Option<&'a str> {
+        let index = self.tag_index.checked_sub(1)?;
+        state.database.get_tag_counts().into_keys().nth(index)
+    }
+
+    fn render_overview(&self, frame: &mut Frame, area: Rect, state: &AppState) {
+        let counts = state.database.status_counts();
+        let lead_time = match state.database.average_lead_time() {
+            Some(lead_time) => format!("{:.1} days", lead_time.as_seconds_f64() / 86400.0),
+            None => "no completed tasks".to_string(),
+        };
+
+        let mut spans = vec![];
+        for (name, count) in [
+            ("Open: ", counts.open),
+            ("Blocked: ", counts.blocked),
+            ("Started: ", counts.started),
+            ("Done: ", counts.done),
+        ] {
+            spans.push(Span::styled(name, BOLD));
+            spans.push(Span::raw(format!("{count}   ")));
+        }
+        let lines = vec![
+            Line::from(spans),
+            Line::from(vec![
+                Span::styled("Average lead time: ", BOLD),
+                Span::raw(lead_time),
+            ]),
+        ];
+
+        let block = Self::block("Overview");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
+    fn render_throughput(&self, frame: &mut Frame, area: Rect, state: &AppState) {
+        let weeks = state.database.weekly_throughput(local_today(), Self::WEEKS);
+        let created = weeks
+            .iter()
+            .enumerate()
+            .map(|(i, week)| (i as f64, week.created as f64))
+            .collect::<Vec<_>>();
+        let completed = weeks
+            .iter()
+            .enumerate()
+            .map(|(i, week)| (i as f64, week.completed as f64))
+            .collect::<Vec<_>>();
+        let max = weeks
+            .iter()
+            .map(|week| week.created.max(week.completed))
+            .max()
+            .unwrap_or_default()
+            .max(1);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("created")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(FG_ACCENT)
+                .data(&created),
+            Dataset::default()
+                .name("completed")
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(FG_GREEN)
+                .data(&completed),
+        ];
+
+        let week_labels = [weeks.first(), weeks.last()]
+            .into_iter()
+            .flatten()
+            .map(|week| Span::styled(week.week_start.to_string(), FG_DIM))
+            .collect();
+        let chart = Chart::new(datasets)
+            .block(Self::block("Tasks per week"))
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, Self::WEEKS.saturating_sub(1) as f64])
+                    .labels(week_labels),
+            )
+            .y_axis(Axis::default().bounds([0.0, max as f64]).labels(vec![
+                Span::styled("0", FG_DIM),
+                Span::styled(max.to_string(), FG_DIM),
+            ]));
+        frame.render_widget(chart, area);
+    }
+
+    fn render_burndown(&self, frame: &mut Frame, area: Rect, state: &AppState) {
+        let tag = self.selected_tag(state);
+        let today = local_today();
+        let first_day = state
+            .database
+            .weekly_throughput(today, Self::WEEKS)
+            .first()
+            .map_or(today, |week| week.week_start);
+        let burndown = state.database.burndown(tag, first_day..=today);
+
+        let block = Self::block(&match tag {
+            Some(tag) => format!("Remaining tasks tagged {tag}"),
+            None => "Remaining tasks".to_string(),
+        });
+
+        // only the most recent days fit if the area is too narrow
+        let visible_days = usize::from(block.inner(area).width);
+        let burndown = &burndown[burndown.len().saturating_sub(visible_days)..];
+        let data = burndown
+            .iter()
+            .map(|&(_, remaining)| remaining as u64)
+            .collect::<Vec<_>>();
+
+        let title = match (burndown.first(), burndown.last()) {
+            (Some((first_date, first)), Some((_, last))) => {
+                format!(" {first} on {first_date}, {last} today ")
+            }
+            _ => String::new(),
+        };
+        let sparkline = Sparkline::default()
+            .block(block.title_bottom(title))
+            .data(&data)
+            .style(FG_ACCENT);
+        frame.render_widget(sparkline, area);
+    }
+
+    fn block(title: &str) -> Block<'static> {
+        Block::default()
+            .title(title.to_string())
+            .style(FG_WHITE)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+    }
+}
+
+impl Component for StatisticsPage {
+    fn pre_render(&self, global_state: &AppState, frame_storage: &mut FrameLocalStorage) {
+        let has_tags = !global_state.database.get_tag_counts().is_empty();
+        frame_storage.register_keybind(KEYBIND_STATS_BURNDOWN_TAG, has_tags);
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        _frame_storage: &FrameLocalStorage,
+    ) {
+        let layout = Layout::default()
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Percentage(60),
+                Constraint::Min(5),
+            ])
+            .direction(Direction::Vertical)
+            .split(area);
+
+        self.render_overview(frame, layout[0], state);
+        self.render_throughput(frame, layout[1], state);
+        self.render_burndown(frame, layout[2], state);
+    }
+
+    fn process_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        _frame_storage: &FrameLocalStorage,
+    ) -> bool {
+        let Some(key) = KEYBIND_STATS_BURNDOWN_TAG.get_match(key) else {
+            return false;
+        };
+
+        // cycle through all tasks and every tag
+        let option_count = state.database.get_tag_counts().len() + 1;
+        self.tag_index = match key {
+            LeftRightKey::Left => (self.tag_index + option_count - 1) % option_count,
+            LeftRightKey::Right => (self.tag_index + 1) % option_count,
+        };
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+    use td_lib::database::Task;
+
+    use super::*;
+
+    #[test]
+    fn burndown_tag_cycles_through_tags() {
+        let mut state = AppState::default();
+        for tag in ["work", "home"] {
+            let mut task = Task::create_now("task".into());
+            task.add_tag(tag.into());
+            state.database.modify("Add task", |db| db.add_task(task));
+        }
+
+        let mut page = StatisticsPage::default();
+        let frame_storage = FrameLocalStorage::default();
+        for code in [KeyCode::Left, KeyCode::Left] {
+            page.process_input(KeyEvent::from(code), &mut state, &frame_storage);
+        }
+        assert_eq!(page.selected_tag(&state), Some("home"));
+        for code in [KeyCode::Right, KeyCode::Right] {
+            page.process_input(KeyEvent::from(code), &mut state, &frame_storage);
+        }
+        assert_eq!(page.selected_tag(&state), None);
+
+        // the burndown is cut off to fit, and tiny areas should not panic
+        for (width, height) in [(80, 30), (10, 12), (0, 0)] {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| page.render(frame, frame.size(), &state, &frame_storage))
+                .unwrap();
+        }
+    }
+}
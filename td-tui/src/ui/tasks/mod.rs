@@ -3,17 +3,29 @@ use ratatui::{
     widgets::{Block, BorderType, Borders},
 };
 
-use self::{task_info::TaskInfoDisplay, task_list::TaskList, task_list_settings::TaskListSettings};
+use td_lib::{
+    database::{operations::Operation, Priority, TaskId},
+    errors::TaskError,
+};
+
+use self::{
+    task_info::TaskInfoDisplay, task_list::TaskList, task_list_settings::TaskListSettings,
+    task_tree::TaskTree,
+};
 use super::{
     constants::{FG_DIM, FG_LIGHT, FG_WHITE},
-    Component,
+    AppState, Component,
+};
+use crate::{
+    keybinds::*,
+    utils::{now, RectExt},
 };
-use crate::{keybinds::*, utils::RectExt};
 
 mod task_info;
 mod task_list;
 mod task_list_settings;
 mod task_search;
+mod task_tree;
 
 pub struct TaskPage {
     list: TaskList,
@@ -78,8 +90,10 @@ impl Component for TaskPage {
             .render(frame, inner_list_area, state, frame_storage);
 
         // split up the info area
-        let (list_settings_area, task_info_area) =
-            info_area.split_y(TaskListSettings::UI_HEIGHT + 2);
+        // the settings grow with the amount of tags, but always leave room for the task info
+        let list_settings_height =
+            (TaskListSettings::ui_height(state) + 2).min(info_area.height / 2);
+        let (list_settings_area, task_info_area) = info_area.split_y(list_settings_height);
 
         // render list settings
         let list_settings_block = Block::default()
@@ -132,3 +146,153 @@ impl Component for TaskPage {
         }
     }
 }
+
+/// Shows the dependency graph as a tree, next to the info of the selected task.
+pub struct TaskTreePage {
+    tree: TaskTree,
+}
+
+impl TaskTreePage {
+    pub fn new() -> Self {
+        Self {
+            tree: TaskTree::new(),
+        }
+    }
+}
+
+impl Component for TaskTreePage {
+    fn pre_render(
+        &self,
+        global_state: &super::AppState,
+        frame_storage: &mut super::FrameLocalStorage,
+    ) {
+        self.tree.pre_render(global_state, frame_storage);
+    }
+
+    fn render(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: ratatui::layout::Rect,
+        state: &super::AppState,
+        frame_storage: &super::FrameLocalStorage,
+    ) {
+        let layout = Layout::default()
+            .constraints([Constraint::Percentage(67), Constraint::Percentage(33)])
+            .direction(Direction::Horizontal)
+            .split(area);
+
+        let tree_area = layout[0];
+        let info_area = layout[1];
+
+        // render task tree
+        let tree_block = Block::default()
+            .title("Dependency Tree")
+            .style(FG_WHITE)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        let inner_tree_area = tree_block.inner(tree_area);
+        frame.render_widget(tree_block, tree_area);
+        self.tree
+            .render(frame, inner_tree_area, state, frame_storage);
+
+        // render task info
+        let task_info_block = Block::default()
+            .title("Task Info")
+            .style(FG_LIGHT)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+        let inner_task_info_area = task_info_block.inner(info_area);
+        frame.render_widget(task_info_block, info_area);
+        TaskInfoDisplay.render(frame, inner_task_info_area, state, frame_storage);
+    }
+
+    fn process_input(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        state: &mut super::AppState,
+        frame_storage: &super::FrameLocalStorage,
+    ) -> bool {
+        self.tree.process_input(key, state, frame_storage)
+    }
+}
+
+/// Starts a new work session for the given task, or ends the running one. The first session marks
+/// the task as started.
+fn toggle_timer(state: &mut AppState, task_id: &TaskId) {
+    let task = &state.database[task_id];
+    let id = task_id.clone();
+    let (description, operation) = match task.is_timer_running() {
+        true => (
+            format!("Stop timer of '{}'", task.title),
+            Operation::StopTimer { id, stopped: now() },
+        ),
+        false => (
+            format!("Start timer of '{}'", task.title),
+            Operation::StartTimer { id, started: now() },
+        ),
+    };
+    _ = state.apply(description, [operation]);
+}
+
+/// Stops the timers of all tasks.
+fn stop_all_timers(state: &mut AppState) {
+    let stopped = now();
+    let operations = state
+        .database
+        .get_all_tasks()
+        .filter(|task| task.is_timer_running())
+        .map(|task| Operation::StopTimer {
+            id: task.id().clone(),
+            stopped,
+        })
+        .collect::<Vec<_>>();
+    _ = state.apply("Stop all timers", operations);
+}
+
+/// Raises or lowers the priority of the given task. Tasks without a priority are less important
+/// than any task with one, so raising them gives them the lowest priority and lowering the lowest
+/// priority clears it.
+fn change_priority(state: &mut AppState, task_id: &TaskId, raise: bool) {
+    let task = &state.database[task_id];
+    let description = match raise {
+        true => format!("Raise priority of '{}'", task.title),
+        false => format!("Lower priority of '{}'", task.title),
+    };
+    let priority = match (task.priority, raise) {
+        (None, true) => Some(Priority::LOWEST),
+        (None, false) => None,
+        (Some(priority), true) => Some(priority.raise().unwrap_or(priority)),
+        (Some(priority), false) => priority.lower(),
+    };
+    let operation = Operation::SetPriority {
+        id: task_id.clone(),
+        priority,
+    };
+    _ = state.apply(description, [operation]);
+}
+
+/// Marks the given task as completed, or as not completed if it already was. Completing a
+/// recurring task also creates its next instance, in the same undo step, and reopening it removes
+/// that instance again.
+fn toggle_completed(state: &mut AppState, task_id: &TaskId) {
+    let task = &state.database[task_id];
+    let title = task.title.clone();
+    let (description, operation) = match task.time_completed {
+        Some(_) => (
+            format!("Reopen '{title}'"),
+            Operation::Reopen {
+                id: task_id.clone(),
+            },
+        ),
+        None => (
+            format!("Complete '{title}'"),
+            Operation::mark_done(task, now()),
+        ),
+    };
+    let result = state.apply(description, [operation]);
+    if let Err(TaskError::NextInstanceChanged(_)) = result {
+        state.set_status_message(format!(
+            "The next instance of '{title}' was changed since, undo completing it instead"
+        ));
+    }
+}
@@ -2,11 +2,19 @@ use ratatui::{
     text::{Line, Span},
     widgets::Paragraph,
 };
-use td_lib::time::{format_description, UtcOffset};
+use td_lib::{
+    database::sessions::format_duration,
+    time::{format_description, OffsetDateTime, UtcOffset},
+};
 
-use crate::ui::{
-    constants::{BOLD, COMPLETED_TASK},
-    AppState, Component, FrameLocalStorage,
+use crate::{
+    ui::{
+        constants::{
+            BOLD, COMPLETED_TASK, FG_DIM, FG_GREEN, FG_RED, NO_STYLE, OVERDUE_TASK, PRIORITY_STYLES,
+        },
+        AppState, Component, FrameLocalStorage,
+    },
+    utils::{local_today, wrap_text},
 };
 
 pub struct TaskInfoDisplay;
@@ -23,8 +31,10 @@ impl Component for TaskInfoDisplay {
             frame.render_widget(Paragraph::new("No task selected"), area);
             return;
         };
-
-        let task = &state.database[&task_id];
+        let Some(task) = state.database.get(&task_id) else {
+            frame.render_widget(Paragraph::new("No task selected"), area);
+            return;
+        };
 
         let date_format =
             format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]")
@@ -42,7 +52,7 @@ impl Component for TaskInfoDisplay {
             ]),
         ];
 
-        if let Some(started_at) = &task.time_started {
+        if let Some(started_at) = &task.time_started() {
             let time_local =
                 started_at.to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC));
             spans.push(Line::from(vec![
@@ -51,6 +61,17 @@ impl Component for TaskInfoDisplay {
             ]));
         }
 
+        if task.is_started() {
+            let mut line = vec![
+                Span::styled("Time spent: ", BOLD),
+                Span::raw(format_duration(task.time_spent(OffsetDateTime::now_utc()))),
+            ];
+            if task.is_timer_running() {
+                line.push(Span::styled(" (timer running)", FG_GREEN));
+            }
+            spans.push(Line::from(line));
+        }
+
         if let Some(completed_at) = &task.time_completed {
             let time_local =
                 completed_at.to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC));
@@ -60,6 +81,131 @@ impl Component for TaskInfoDisplay {
             ]));
         }
 
+        if let Some(due_date) = task.due_date {
+            let style = if task.time_completed.is_none() && due_date < local_today() {
+                OVERDUE_TASK
+            } else {
+                NO_STYLE
+            };
+            spans.push(Line::from(vec![
+                Span::styled("Due: ", BOLD),
+                Span::styled(format!("{}, {due_date}", due_date.weekday()), style),
+            ]));
+        }
+
+        if let Some(priority) = task.priority {
+            spans.push(Line::from(vec![
+                Span::styled("Priority: ", BOLD),
+                Span::styled(
+                    priority.to_string(),
+                    PRIORITY_STYLES[usize::from(priority.value())],
+                ),
+            ]));
+        }
+
+        // show how much work is waiting on this task
+        if task.time_completed.is_none() {
+            let effective_priority = state.database.effective_priority(&task_id);
+            if let Some(priority) = effective_priority.filter(|p| Some(*p) != task.priority) {
+                spans.push(Line::from(vec![
+                    Span::styled("Effective priority: ", BOLD),
+                    Span::styled(
+                        priority.to_string(),
+                        PRIORITY_STYLES[usize::from(priority.value())],
+                    ),
+                    Span::styled(" (inherited from dependents)", FG_DIM),
+                ]));
+            }
+
+            let dependent_count = state.database.get_transitive_dependents(&task_id).len();
+            if dependent_count > 0 {
+                spans.push(Line::from(vec![
+                    Span::styled("Unblocks: ", BOLD),
+                    Span::raw(match dependent_count {
+                        1 => "1 task".to_string(),
+                        _ => format!("{dependent_count} tasks"),
+                    }),
+                ]));
+            }
+        }
+
+        if let Some(estimate) = task.estimate {
+            spans.push(Line::from(vec![
+                Span::styled("Estimate: ", BOLD),
+                Span::raw(estimate.to_string()),
+            ]));
+        }
+
+        // include the work that has to be done before this task can be started
+        let dependency_count = match task.time_completed {
+            Some(_) => 0,
+            None => state.database.get_transitive_dependencies(&task_id).len(),
+        };
+        if dependency_count > 0 {
+            let remaining = state.database.remaining_estimate(&task_id);
+            let mut note = match dependency_count {
+                1 => " (including 1 dependency".to_string(),
+                _ => format!(" (including {dependency_count} dependencies"),
+            };
+            if remaining.unestimated_count > 0 {
+                note += &format!(", {} not estimated", remaining.unestimated_count);
+            }
+            note += ")";
+
+            spans.push(Line::from(vec![
+                Span::styled("Remaining: ", BOLD),
+                Span::raw(remaining.total.to_string()),
+                Span::styled(note, FG_DIM),
+            ]));
+        }
+
+        if let Some(recurrence) = task.recurrence {
+            spans.push(Line::from(vec![
+                Span::styled("Repeats: ", BOLD),
+                Span::raw(recurrence.to_string()),
+            ]));
+        }
+
+        // warn about dependencies that are due after this task
+        let conflicts = state.database.get_due_date_conflicts(&task_id);
+        if !conflicts.is_empty() {
+            spans.extend([
+                Line::default(),
+                Line::from(Span::styled(
+                    "⚠ Dependencies due after this task:",
+                    FG_RED.patch(BOLD),
+                )),
+            ]);
+            spans.extend(conflicts.into_iter().map(|task| {
+                Line::from(vec![
+                    Span::raw("- "),
+                    Span::raw(&task.title),
+                    Span::styled(
+                        format!(
+                            " (due {})",
+                            task.due_date.expect("conflicts have a due date")
+                        ),
+                        FG_DIM,
+                    ),
+                ])
+            }));
+        }
+
+        // add description
+        if !task.description.is_empty() {
+            spans.extend([
+                Line::default(),
+                Line::from(Span::styled("Description:", BOLD)),
+            ]);
+
+            spans.extend(
+                task.description
+                    .lines()
+                    .flat_map(|line| wrap_text(line, area.width))
+                    .map(Line::from),
+            );
+        }
+
         // add tags
         if !task.tags.is_empty() {
             spans.extend([Line::default(), Line::from(Span::styled("Tags:", BOLD))]);
@@ -9,11 +9,16 @@ use ratatui::{
     Frame,
 };
 use td_lib::{
-    database::{Task, TaskId},
-    time::OffsetDateTime,
+    database::{estimate::Estimate, operations::Operation, recurrence::Recurrence, Task, TaskId},
+    dates::parse_date,
+    errors::{DependencyCycleError, TaskError},
+    time::{format_description, Date},
 };
 
-use super::task_search::TaskSearchBarComponent;
+use super::{
+    change_priority, stop_all_timers, task_search::TaskSearchBarComponent, toggle_completed,
+    toggle_timer,
+};
 use crate::{
     keybinds::*,
     ui::{
@@ -22,7 +27,7 @@ use crate::{
         modal::*,
         AppState, Component, FrameLocalStorage,
     },
-    utils::RectExt,
+    utils::{local_today, RectExt},
 };
 
 pub struct TaskList {
@@ -32,9 +37,20 @@ pub struct TaskList {
     create_task_modal: CollectionKey<TextInputModal>,
     new_tag_modal: CollectionKey<TextInputModal>,
     rename_task_modal: CollectionKey<TextInputModal>,
+    edit_description_modal: CollectionKey<TextAreaModal>,
+    due_date_modal: CollectionKey<ParsedInputModal<Option<Date>>>,
+    recurrence_modal: CollectionKey<ParsedInputModal<Option<Recurrence>>>,
+    estimate_modal: CollectionKey<ParsedInputModal<Option<Estimate>>>,
     delete_task_modal: CollectionKey<ConfirmationModal>,
     edit_modal: CollectionKey<KeybindSelectModal>,
     search_box_depend_on: CollectionKey<ListSearchModal<TaskId>>,
+    dependency_cycle_modal: CollectionKey<MessageModal>,
+    search_box_remove_dependency: CollectionKey<ListSearchModal<(TaskId, TaskId)>>,
+    search_box_remove_tag: CollectionKey<ListSearchModal<String>>,
+    search_box_rename_tag: CollectionKey<ListSearchModal<String>>,
+    rename_tag_modal: CollectionKey<TextInputModal>,
+    /// The tag that is being renamed while [`Self::rename_tag_modal`] is open.
+    tag_to_rename: Option<String>,
 }
 
 enum TaskListFocus {
@@ -44,6 +60,8 @@ enum TaskListFocus {
 
 impl TaskList {
     const SCROLL_PAGE_UP_DOWN: usize = 32;
+    /// Tasks due within this many days are highlighted.
+    const DUE_SOON_DAYS: i64 = 2;
 
     pub fn new() -> Self {
         let mut modal_collection = ComponentCollection::default();
@@ -55,6 +73,23 @@ impl TaskList {
             new_tag_modal: modal_collection.insert(TextInputModal::new("Add new tag".to_string())),
             rename_task_modal: modal_collection
                 .insert(TextInputModal::new("Rename task".to_string())),
+            edit_description_modal: modal_collection
+                .insert(TextAreaModal::new("Edit description".to_string())),
+            due_date_modal: modal_collection.insert(ParsedInputModal::new(
+                "Set due date".to_string(),
+                parse_due_date,
+                describe_due_date,
+            )),
+            recurrence_modal: modal_collection.insert(ParsedInputModal::new(
+                "Set recurrence".to_string(),
+                parse_recurrence,
+                describe_recurrence,
+            )),
+            estimate_modal: modal_collection.insert(ParsedInputModal::new(
+                "Set estimate".to_string(),
+                parse_estimate,
+                describe_estimate,
+            )),
             delete_task_modal: modal_collection.insert(
                 ConfirmationModal::new("Do you want to delete this task?".to_string())
                     .with_title("Delete Task".to_string()),
@@ -63,6 +98,20 @@ impl TaskList {
             search_box_depend_on: modal_collection.insert(ListSearchModal::new(
                 "Choose which task to depend on".to_string(),
             )),
+            dependency_cycle_modal: modal_collection
+                .insert(MessageModal::new("Dependency cycle".to_string())),
+            search_box_remove_dependency: modal_collection.insert(ListSearchModal::new(
+                "Choose which dependency to remove".to_string(),
+            )),
+            search_box_remove_tag: modal_collection.insert(ListSearchModal::new(
+                "Choose which tag to remove".to_string(),
+            )),
+            search_box_rename_tag: modal_collection.insert(ListSearchModal::new(
+                "Choose which tag to rename".to_string(),
+            )),
+            rename_tag_modal: modal_collection
+                .insert(TextInputModal::new("Rename tag on all tasks".to_string())),
+            tag_to_rename: None,
             modals: modal_collection,
         }
     }
@@ -71,21 +120,22 @@ impl TaskList {
         let mut tasks = state.database.get_all_tasks().cloned().collect::<Vec<_>>();
 
         // sort
-        tasks.sort_by(|a, b| a.time_created.cmp(&b.time_created));
+        tasks.sort_by_key(|t| t.time_created);
         if !state.sort_oldest_first {
             tasks.reverse();
         }
+        state.database.sort_tasks(&mut tasks, state.sort_order);
 
         // filter
         tasks.retain(|x| state.get_task_filter_predicate().eval(x));
         if state.filter_search {
-            tasks.retain(|t| self.search_bar.filter(t));
+            tasks.retain(|t| self.search_bar.filter(&state.database, t));
         }
 
         tasks
     }
 
-    fn task_to_span(&self, state: &AppState, task: &Task) -> Line {
+    fn task_to_span(&self, state: &AppState, task: &Task) -> Line<'_> {
         let mut spans = vec![];
 
         let dependents_count = state.database.get_inverse_dependencies(task.id()).count();
@@ -113,15 +163,28 @@ impl TaskList {
             spans.push(Span::raw(" "));
         }
 
+        if let Some(priority) = task.priority {
+            spans.push(Span::styled(
+                format!("{priority} "),
+                PRIORITY_STYLES[usize::from(priority.value())],
+            ));
+        }
+
         // add title
         let text_style = if task.time_completed.is_some() {
             LIST_STYLE.patch(COMPLETED_TASK)
-        } else if task.time_started.is_some() {
+        } else if task.is_started() {
             LIST_STYLE.patch(STARTED_TASK)
         } else {
             LIST_STYLE
         };
         spans.push(Span::styled(task.title.clone(), text_style));
+        if task.is_timer_running() {
+            spans.push(Span::styled(" ⏱", FG_GREEN.patch(BOLD)));
+        }
+        if task.recurrence.is_some() {
+            spans.push(Span::styled(" ↻", FG_DIM));
+        }
 
         // add tags
         for tag in &task.tags {
@@ -129,6 +192,33 @@ impl TaskList {
             spans.push(Span::styled(tag.clone(), FG_DIM.patch(ITALIC)));
         }
 
+        // add due date
+        if let Some(due_date) = task.due_date {
+            let days_left = (due_date - local_today()).whole_days();
+            let text = match days_left {
+                ..=-1 => format!("overdue {}d", -days_left),
+                0 => "due today".to_string(),
+                1 => "due tomorrow".to_string(),
+                2..=7 => format!("due in {days_left}d"),
+                _ => format!("due {due_date}"),
+            };
+            let style = if task.time_completed.is_some() {
+                FG_DIM
+            } else if days_left < 0 {
+                OVERDUE_TASK
+            } else if days_left <= Self::DUE_SOON_DAYS {
+                DUE_SOON_TASK
+            } else {
+                FG_DIM
+            };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(text, style));
+
+            if !state.database.get_due_date_conflicts(task.id()).is_empty() {
+                spans.push(Span::styled(" ⚠", FG_RED.patch(BOLD)));
+            }
+        }
+
         spans.into()
     }
 
@@ -147,7 +237,7 @@ impl Component for TaskList {
             TaskListFocus::SearchBar => {
                 // select top-most task if possible. it's better than having none selected
                 let task_list = self.get_task_list(global_state);
-                frame_storage.selected_task_id = task_list.get(0).map(|x| x.id().clone());
+                frame_storage.selected_task_id = task_list.first().map(|x| x.id().clone());
 
                 // NOTE: there should never be an open modal with the searchbar selected, but this
                 // makes sure that they would work if it happened regardless.
@@ -168,8 +258,10 @@ impl Component for TaskList {
                 frame_storage.register_keybind(KEYBIND_CONTROLS_LIST_NAV_EXT, task_list.len() >= 2);
 
                 let is_task_selected = frame_storage.selected_task_id.is_some();
-                frame_storage.register_keybind(KEYBIND_TASK_MARK_STARTED, is_task_selected);
+                frame_storage.register_keybind(KEYBIND_TASK_TOGGLE_TIMER, is_task_selected);
                 frame_storage.register_keybind(KEYBIND_TASK_MARK_DONE, is_task_selected);
+                frame_storage.register_keybind(KEYBIND_TASK_RAISE_PRIORITY, is_task_selected);
+                frame_storage.register_keybind(KEYBIND_TASK_LOWER_PRIORITY, is_task_selected);
                 frame_storage.register_keybind(KEYBIND_TASK_NEW, true);
                 frame_storage.register_keybind(KEYBIND_TASK_DELETE, is_task_selected);
                 frame_storage.register_keybind(KEYBIND_TASK_ADD_TAG, is_task_selected);
@@ -177,6 +269,9 @@ impl Component for TaskList {
                 frame_storage.register_keybind(KEYBIND_TASK_RENAME, is_task_selected);
                 frame_storage.register_keybind(KEYBIND_TASK_EDIT, is_task_selected);
                 frame_storage.register_keybind(KEYBIND_TASK_TOGGLE_SEARCH, true);
+
+                let any_timer_running = global_state.database.get_running_timers().next().is_some();
+                frame_storage.register_keybind(KEYBIND_TASK_STOP_ALL_TIMERS, any_timer_running);
             }
         }
     }
@@ -268,33 +363,19 @@ impl Component for TaskList {
                 // take our own input
                 // start by checking actions that require a task to present
                 let handled_by_task = if !tasks.is_empty() {
-                    if KEYBIND_TASK_MARK_STARTED.is_match(key) {
-                        state.database.modify(|db| {
-                            let task = &mut db[tasks[task_index].id()];
-                            if task.time_started.is_none() {
-                                task.time_started = Some(
-                                    OffsetDateTime::now_local()
-                                        .unwrap_or_else(|_| OffsetDateTime::now_utc()),
-                                );
-                            } else {
-                                task.time_started = None;
-                            }
-                        });
+                    if KEYBIND_TASK_TOGGLE_TIMER.is_match(key) {
+                        toggle_timer(state, tasks[task_index].id());
 
                         true
                     } else if KEYBIND_TASK_MARK_DONE.is_match(key) {
-                        state.database.modify(|db| {
-                            let task = &mut db[tasks[task_index].id()];
-                            if task.time_completed.is_none() {
-                                task.time_completed = Some(
-                                    OffsetDateTime::now_local()
-                                        .unwrap_or_else(|_| OffsetDateTime::now_utc()),
-                                );
-                            } else {
-                                task.time_completed = None;
-                            }
-                        });
+                        toggle_completed(state, tasks[task_index].id());
 
+                        true
+                    } else if KEYBIND_TASK_RAISE_PRIORITY.is_match(key) {
+                        change_priority(state, tasks[task_index].id(), true);
+                        true
+                    } else if KEYBIND_TASK_LOWER_PRIORITY.is_match(key) {
+                        change_priority(state, tasks[task_index].id(), false);
                         true
                     } else if KEYBIND_TASK_RENAME.is_match(key) {
                         self.modals[self.rename_task_modal]
@@ -318,9 +399,16 @@ impl Component for TaskList {
                     } else if KEYBIND_TASK_EDIT.is_match(key) {
                         self.modals[self.edit_modal].open(vec![
                             KEYBIND_TASK_RENAME.clone(),
+                            KEYBIND_TASK_EDIT_DESCRIPTION.clone(),
+                            KEYBIND_TASK_SET_DUE_DATE.clone(),
+                            KEYBIND_TASK_SET_RECURRENCE.clone(),
+                            KEYBIND_TASK_SET_ESTIMATE.clone(),
                             KEYBIND_TASK_DELETE.clone(),
                             KEYBIND_TASK_ADD_DEPENDENCY.clone(),
+                            KEYBIND_TASK_REMOVE_DEPENDENCY.clone(),
                             KEYBIND_TASK_ADD_TAG.clone(),
+                            KEYBIND_TASK_REMOVE_TAG.clone(),
+                            KEYBIND_TASK_RENAME_TAG.clone(),
                         ]);
                         true
                     } else {
@@ -335,6 +423,9 @@ impl Component for TaskList {
                     || if KEYBIND_TASK_NEW.is_match(key) {
                         self.modals[self.create_task_modal].open();
                         true
+                    } else if KEYBIND_TASK_STOP_ALL_TIMERS.is_match(key) {
+                        stop_all_timers(state);
+                        true
                     } else if KEYBIND_TASK_TOGGLE_SEARCH.is_match(key) {
                         state.filter_search = !state.filter_search;
 
@@ -411,6 +502,35 @@ impl TaskList {
                             .open_with_text(tasks[task_index].title.clone());
                         return true;
                     }
+                    _ if selected == *KEYBIND_TASK_EDIT_DESCRIPTION => {
+                        self.modals[self.edit_description_modal]
+                            .open_with_text(tasks[task_index].description.clone());
+                        return true;
+                    }
+                    _ if selected == *KEYBIND_TASK_SET_DUE_DATE => {
+                        let text = tasks[task_index]
+                            .due_date
+                            .map(|d| d.to_string())
+                            .unwrap_or_default();
+                        self.modals[self.due_date_modal].open_with_text(text);
+                        return true;
+                    }
+                    _ if selected == *KEYBIND_TASK_SET_RECURRENCE => {
+                        let text = tasks[task_index]
+                            .recurrence
+                            .map(|r| r.to_string())
+                            .unwrap_or_default();
+                        self.modals[self.recurrence_modal].open_with_text(text);
+                        return true;
+                    }
+                    _ if selected == *KEYBIND_TASK_SET_ESTIMATE => {
+                        let text = tasks[task_index]
+                            .estimate
+                            .map(|e| e.to_string())
+                            .unwrap_or_default();
+                        self.modals[self.estimate_modal].open_with_text(text);
+                        return true;
+                    }
                     _ if selected == *KEYBIND_TASK_DELETE => {
                         self.modals[self.delete_task_modal].open(true);
                         return true;
@@ -420,6 +540,11 @@ impl TaskList {
                         Self::open_add_dependency_dialog(modal, state, task_index, tasks);
                         return true;
                     }
+                    _ if selected == *KEYBIND_TASK_REMOVE_DEPENDENCY => {
+                        let modal = &mut self.modals[self.search_box_remove_dependency];
+                        Self::open_remove_dependency_dialog(modal, state, task_index, tasks);
+                        return true;
+                    }
                     _ if selected == *KEYBIND_TASK_ADD_TAG => {
                         if !tasks.is_empty() {
                             // add tag to currently selected task
@@ -427,6 +552,25 @@ impl TaskList {
                         }
                         return true;
                     }
+                    _ if selected == *KEYBIND_TASK_REMOVE_TAG => {
+                        let tags = tasks[task_index]
+                            .tags
+                            .iter()
+                            .map(|tag| (tag.clone(), tag.clone()))
+                            .collect();
+                        self.modals[self.search_box_remove_tag].open(tags);
+                        return true;
+                    }
+                    _ if selected == *KEYBIND_TASK_RENAME_TAG => {
+                        let tags = state
+                            .database
+                            .get_tag_counts()
+                            .into_iter()
+                            .map(|(tag, count)| (tag.to_string(), format!("{tag} ({count})")))
+                            .collect();
+                        self.modals[self.search_box_rename_tag].open(tags);
+                        return true;
+                    }
                     _ => (),
                 }
             }
@@ -437,9 +581,9 @@ impl TaskList {
             // popup is open
             if KEYBIND_MODAL_SUBMIT.is_match(key) {
                 if let Some(text) = self.modals[self.create_task_modal].close() {
-                    state
-                        .database
-                        .modify(|x| x.add_task(Task::create_now(text)));
+                    let description = format!("Create task '{text}'");
+                    let task = Task::create_now(text);
+                    _ = state.apply(description, [Operation::AddTask { task }]);
                 }
                 true
             } else {
@@ -449,10 +593,65 @@ impl TaskList {
             // popup is open
             if KEYBIND_MODAL_SUBMIT.is_match(key) {
                 if let Some(text) = self.modals[self.rename_task_modal].close() {
-                    state.database.modify(|db| {
-                        let selected_task = &mut db[tasks[task_index].id()];
-                        selected_task.title = text;
-                    });
+                    let description = format!("Rename task '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::Rename { id, title: text }]);
+                }
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.edit_description_modal].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(text) = self.modals[self.edit_description_modal].close() {
+                    let description = format!("Edit description of '{}'", tasks[task_index].title);
+                    let operation = Operation::SetDescription {
+                        id: tasks[task_index].id().clone(),
+                        description: text.trim_end().to_string(),
+                    };
+                    _ = state.apply(description, [operation]);
+                }
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.due_date_modal].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                // invalid dates keep the popup open, the modal shows why they are invalid
+                if let Some(Ok(due_date)) = self.modals[self.due_date_modal].value() {
+                    self.modals[self.due_date_modal].close();
+                    let description = format!("Set due date of '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::SetDueDate { id, due_date }]);
+                }
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.recurrence_modal].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                // like the due date, invalid recurrences keep the popup open
+                if let Some(Ok(recurrence)) = self.modals[self.recurrence_modal].value() {
+                    self.modals[self.recurrence_modal].close();
+                    let description = format!("Set recurrence of '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::SetRecurrence { id, recurrence }]);
+                }
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.estimate_modal].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(Ok(estimate)) = self.modals[self.estimate_modal].value() {
+                    self.modals[self.estimate_modal].close();
+                    let description = format!("Set estimate of '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::SetEstimate { id, estimate }]);
                 }
                 true
             } else {
@@ -463,9 +662,9 @@ impl TaskList {
             if KEYBIND_MODAL_SUBMIT.is_match(key) {
                 if self.modals[self.delete_task_modal].close() && !tasks.is_empty() {
                     // delete
-                    state
-                        .database
-                        .modify(|x| x.remove_task(tasks[task_index].id()));
+                    let description = format!("Delete task '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::Delete { id }]);
                 }
                 true
             } else {
@@ -475,10 +674,9 @@ impl TaskList {
             // popup is open
             if KEYBIND_MODAL_SUBMIT.is_match(key) {
                 if let Some(text) = self.modals[self.new_tag_modal].close() {
-                    state.database.modify(|db| {
-                        let selected_task = &mut db[tasks[task_index].id()];
-                        selected_task.tags.push(text);
-                    });
+                    let description = format!("Add tag '{text}' to '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::AddTag { id, tag: text }]);
                 }
                 true
             } else {
@@ -488,9 +686,81 @@ impl TaskList {
             // popup is open
             if KEYBIND_MODAL_SUBMIT.is_match(key) {
                 if let Some(selected_task_id) = self.modals[self.search_box_depend_on].close() {
-                    state
-                        .database
-                        .modify(|x| x.add_dependency(tasks[task_index].id(), &selected_task_id));
+                    let description = format!(
+                        "Add dependency '{}' → '{}'",
+                        tasks[task_index].title, state.database[&selected_task_id].title
+                    );
+                    let operation = Operation::AddDependency {
+                        from: tasks[task_index].id().clone(),
+                        to: selected_task_id,
+                    };
+                    let result = state.apply(description, [operation]);
+
+                    if let Err(TaskError::DependencyCycle(DependencyCycleError(cycle))) = result {
+                        let cycle_titles = cycle
+                            .iter()
+                            .chain(cycle.first())
+                            .map(|id| state.database[id].title.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n→ ");
+                        self.modals[self.dependency_cycle_modal].open(format!(
+                            "This dependency was not added because it would create a cycle:\n\n{cycle_titles}"
+                        ));
+                    }
+                }
+
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.search_box_remove_dependency].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some((from, to)) = self.modals[self.search_box_remove_dependency].close() {
+                    let description = format!(
+                        "Remove dependency '{}' → '{}'",
+                        state.database[&from].title, state.database[&to].title
+                    );
+                    _ = state.apply(description, [Operation::RemoveDependency { from, to }]);
+                }
+
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.search_box_remove_tag].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(tag) = self.modals[self.search_box_remove_tag].close() {
+                    let description =
+                        format!("Remove tag '{tag}' from '{}'", tasks[task_index].title);
+                    let id = tasks[task_index].id().clone();
+                    _ = state.apply(description, [Operation::RemoveTag { id, tag }]);
+                }
+
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.search_box_rename_tag].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let Some(tag) = self.modals[self.search_box_rename_tag].close() {
+                    self.modals[self.rename_tag_modal].open_with_text(tag.clone());
+                    self.tag_to_rename = Some(tag);
+                }
+
+                true
+            } else {
+                false
+            }
+        } else if self.modals[self.rename_tag_modal].is_open() {
+            // popup is open
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                let new_name = self.modals[self.rename_tag_modal].close();
+                if let (Some(old_name), Some(new_name)) = (self.tag_to_rename.take(), new_name) {
+                    let description = format!("Rename tag '{old_name}' to '{new_name}'");
+                    _ = state.apply(description, [Operation::RenameTag { old_name, new_name }]);
                 }
 
                 true
@@ -523,4 +793,79 @@ impl TaskList {
             .collect();
         modal.open(candidate_tasks);
     }
+
+    fn open_remove_dependency_dialog(
+        modal: &mut ListSearchModal<(TaskId, TaskId)>,
+        state: &AppState,
+        task_index: usize,
+        tasks: &[Task],
+    ) {
+        let selected = &tasks[task_index];
+        let dependencies = state.database.get_dependencies(selected.id()).map(|t| {
+            (
+                (selected.id().clone(), t.id().clone()),
+                format!("Depends on: {}", t.title),
+            )
+        });
+        let dependents = state
+            .database
+            .get_inverse_dependencies(selected.id())
+            .map(|t| {
+                (
+                    (t.id().clone(), selected.id().clone()),
+                    format!("Depended on by: {}", t.title),
+                )
+            });
+        modal.open(dependencies.chain(dependents).collect());
+    }
+}
+
+fn parse_due_date(text: &str) -> Result<Option<Date>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_date(text, local_today())
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+fn describe_due_date(due_date: &Option<Date>) -> String {
+    let Some(due_date) = due_date else {
+        return "No due date".to_string();
+    };
+    let format = format_description::parse("[weekday], [year]-[month]-[day]")
+        .expect("valid hardcoded date format");
+    due_date.format(&format).unwrap_or_default()
+}
+
+fn parse_recurrence(text: &str) -> Result<Option<Recurrence>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    text.parse::<Recurrence>()
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+fn describe_recurrence(recurrence: &Option<Recurrence>) -> String {
+    match recurrence {
+        Some(recurrence) => format!("Repeats {recurrence}"),
+        None => "Does not repeat".to_string(),
+    }
+}
+
+fn parse_estimate(text: &str) -> Result<Option<Estimate>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    text.parse::<Estimate>()
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+fn describe_estimate(estimate: &Option<Estimate>) -> String {
+    match estimate {
+        Some(estimate) => format!("Takes {estimate}"),
+        None => "No estimate".to_string(),
+    }
 }
@@ -1,10 +1,11 @@
 use ratatui::widgets::Paragraph;
+use td_lib::database::analysis::TaskOrder;
 
 use crate::{
     keybinds::*,
     ui::{
-        constants::{LIST_HIGHLIGHT_STYLE, NO_STYLE, SETTINGS_HEADER},
-        Component,
+        constants::{FG_DIM, LIST_HIGHLIGHT_STYLE, NO_STYLE, SETTINGS_HEADER},
+        AppState, Component, TagFilter,
     },
     utils::RectExt,
 };
@@ -15,27 +16,57 @@ pub struct TaskListSettings {
 }
 
 impl TaskListSettings {
-    pub const UI_HEIGHT: u16 = Self::SETTING_COUNT as u16 + 2 + 1;
+    /// The height of the settings above the tag filters.
+    const FIXED_UI_HEIGHT: u16 = Self::SETTING_COUNT as u16 + 2 + 1;
 
-    const SETTING_COUNT: usize = 4;
+    /// The amount of settings above the tag filters. Tag filters are indexed after these.
+    const SETTING_COUNT: usize = 5;
 
     const INDEX_SORT_OLDEST: usize = 0;
-    const INDEX_FILTER_COMPLETED: usize = 1;
-    const INDEX_FILTER_UNACTIONABLE: usize = 2;
-    const INDEX_FILTER_SEARCH: usize = 3;
+    const INDEX_SORT_ORDER: usize = 1;
+    const INDEX_FILTER_COMPLETED: usize = 2;
+    const INDEX_FILTER_UNACTIONABLE: usize = 3;
+    const INDEX_FILTER_SEARCH: usize = 4;
+
+    /// The height needed to show all settings, including a row for every tag.
+    pub fn ui_height(state: &AppState) -> u16 {
+        let tag_rows = Self::get_tags(state).len().max(1) as u16;
+        Self::FIXED_UI_HEIGHT + 2 + tag_rows
+    }
+
+    /// Gets every tag that can be filtered on, along with how many tasks have it. This includes
+    /// tags that are filtered on but no longer exist, so their filter can be cleared.
+    fn get_tags(state: &AppState) -> Vec<(String, usize)> {
+        let mut tag_counts = state
+            .database
+            .get_tag_counts()
+            .into_iter()
+            .map(|(tag, count)| (tag.to_string(), count))
+            .collect::<Vec<_>>();
+        for tag in state.tag_filters.keys() {
+            if let Err(index) = tag_counts.binary_search_by(|(t, _)| t.cmp(tag)) {
+                tag_counts.insert(index, (tag.clone(), 0));
+            }
+        }
+        tag_counts
+    }
+
+    fn setting_count(state: &AppState) -> usize {
+        Self::SETTING_COUNT + Self::get_tags(state).len()
+    }
 }
 
 impl Component for TaskListSettings {
     fn pre_render(
         &self,
-        _global_state: &crate::ui::AppState,
+        global_state: &crate::ui::AppState,
         frame_storage: &mut crate::ui::FrameLocalStorage,
     ) {
-        frame_storage.register_keybind(KEYBIND_CONTROLS_LIST_NAV, Self::SETTING_COUNT > 1);
-
-        if self.index == Self::INDEX_SORT_OLDEST || self.index == Self::INDEX_FILTER_COMPLETED {
-            frame_storage.register_keybind(KEYBIND_CONTROLS_CHECKBOX_TOGGLE, true);
-        }
+        frame_storage.register_keybind(
+            KEYBIND_CONTROLS_LIST_NAV,
+            Self::setting_count(global_state) > 1,
+        );
+        frame_storage.register_keybind(KEYBIND_CONTROLS_CHECKBOX_TOGGLE, true);
     }
 
     fn render(
@@ -45,7 +76,8 @@ impl Component for TaskListSettings {
         state: &crate::ui::AppState,
         _frame_storage: &crate::ui::FrameLocalStorage,
     ) {
-        let (area_sorting, area_filter) = area.split_y(3);
+        let (area_sorting, area_filter) = area.split_y(4);
+        let (area_filter, area_tags) = area_filter.split_y(Self::FIXED_UI_HEIGHT - 4 + 1);
 
         let checkbox = |b: bool| if b { 'x' } else { ' ' };
         let list_style = |i: usize| {
@@ -69,6 +101,11 @@ impl Component for TaskListSettings {
             .style(list_style(Self::INDEX_SORT_OLDEST)),
             area_sorting.slice_y(1..=1),
         );
+        frame.render_widget(
+            Paragraph::new(format!(" Sort by: < {} >", state.sort_order))
+                .style(list_style(Self::INDEX_SORT_ORDER)),
+            area_sorting.slice_y(2..=2),
+        );
 
         // Filter
         frame.render_widget(
@@ -96,6 +133,38 @@ impl Component for TaskListSettings {
                 .style(list_style(Self::INDEX_FILTER_SEARCH)),
             area_filter.slice_y(3..=3),
         );
+
+        // Tags
+        frame.render_widget(
+            Paragraph::new("Tags:").style(SETTINGS_HEADER),
+            area_tags.slice_y(0..=0).take_x("Tags:".len() as u16),
+        );
+        let area_tag_list = area_tags.skip_y(1);
+        let tags = Self::get_tags(state);
+        if tags.is_empty() {
+            frame.render_widget(
+                Paragraph::new(" No tags").style(FG_DIM),
+                area_tag_list.take_y(1),
+            );
+        }
+
+        // scroll so the selected tag is always visible
+        let visible_count = area_tag_list.height as usize;
+        let selected_tag_index = self.index.saturating_sub(Self::SETTING_COUNT);
+        let scroll = (selected_tag_index + 1).saturating_sub(visible_count);
+        for (row, (tag, count)) in tags.iter().enumerate().skip(scroll).take(visible_count) {
+            let filter = match state.tag_filters.get(tag) {
+                Some(TagFilter::Include) => '+',
+                Some(TagFilter::Exclude) => '-',
+                None => ' ',
+            };
+            let y = (row - scroll) as u16;
+            frame.render_widget(
+                Paragraph::new(format!(" [{filter}] {tag} ({count})"))
+                    .style(list_style(Self::SETTING_COUNT + row)),
+                area_tag_list.slice_y(y..=y),
+            );
+        }
     }
 
     fn process_input(
@@ -104,14 +173,15 @@ impl Component for TaskListSettings {
         state: &mut crate::ui::AppState,
         _frame_storage: &crate::ui::FrameLocalStorage,
     ) -> bool {
+        let setting_count = Self::setting_count(state);
         if let Some(key) = KEYBIND_CONTROLS_LIST_NAV.get_match(key) {
             match key {
                 UpDownKey::Up => {
-                    self.index = self.index.saturating_sub(1).min(Self::SETTING_COUNT - 1);
+                    self.index = self.index.saturating_sub(1).min(setting_count - 1);
                     true
                 }
                 UpDownKey::Down => {
-                    self.index = self.index.saturating_add(1).min(Self::SETTING_COUNT - 1);
+                    self.index = self.index.saturating_add(1).min(setting_count - 1);
                     true
                 }
             }
@@ -121,6 +191,15 @@ impl Component for TaskListSettings {
                     state.sort_oldest_first = !state.sort_oldest_first;
                     true
                 }
+                Self::INDEX_SORT_ORDER if KEYBIND_CONTROLS_CHECKBOX_TOGGLE.is_match(key) => {
+                    // cycle through every order
+                    let index = TaskOrder::ALL
+                        .iter()
+                        .position(|&order| order == state.sort_order)
+                        .unwrap_or_default();
+                    state.sort_order = TaskOrder::ALL[(index + 1) % TaskOrder::ALL.len()];
+                    true
+                }
                 Self::INDEX_FILTER_COMPLETED if KEYBIND_CONTROLS_CHECKBOX_TOGGLE.is_match(key) => {
                     state.filter_completed = !state.filter_completed;
                     true
@@ -135,6 +214,28 @@ impl Component for TaskListSettings {
                     state.filter_search = !state.filter_search;
                     true
                 }
+                index if KEYBIND_CONTROLS_CHECKBOX_TOGGLE.is_match(key) => {
+                    let Some((tag, _)) = Self::get_tags(state)
+                        .into_iter()
+                        .nth(index - Self::SETTING_COUNT)
+                    else {
+                        return false;
+                    };
+
+                    // cycle through include, exclude and no filter
+                    match state.tag_filters.get(&tag) {
+                        None => {
+                            state.tag_filters.insert(tag, TagFilter::Include);
+                        }
+                        Some(TagFilter::Include) => {
+                            state.tag_filters.insert(tag, TagFilter::Exclude);
+                        }
+                        Some(TagFilter::Exclude) => {
+                            state.tag_filters.remove(&tag);
+                        }
+                    }
+                    true
+                }
                 _ => false,
             }
         }
@@ -1,9 +1,20 @@
-use td_lib::database::Task;
+use ratatui::widgets::Paragraph;
+use td_lib::{
+    database::{query::Query, Database, Task},
+    errors::QueryParseError,
+};
 
-use crate::ui::{input::TextBoxComponent, Component};
+use crate::{
+    ui::{constants::FG_RED, input::TextBoxComponent, Component},
+    utils::RectExt,
+};
 
 pub struct TaskSearchBarComponent {
     textbox: TextBoxComponent,
+    /// The last query that was parsed successfully.
+    query: Query,
+    /// The error from parsing the current text, if it is not a valid query.
+    error: Option<QueryParseError>,
 }
 
 impl Default for TaskSearchBarComponent {
@@ -12,16 +23,15 @@ impl Default for TaskSearchBarComponent {
             textbox: TextBoxComponent::default()
                 .with_background(true)
                 .with_focus(false),
+            query: Query::default(),
+            error: None,
         }
     }
 }
 
 impl TaskSearchBarComponent {
-    pub fn filter(&self, task: &Task) -> bool {
-        // PERF: allocates new string every time which is fairly wasteful
-        task.title
-            .to_lowercase()
-            .contains(&self.textbox.text().to_lowercase())
+    pub fn filter(&self, database: &Database, task: &Task) -> bool {
+        self.query.matches(database, task)
     }
 
     pub fn set_focus(&mut self, value: bool) {
@@ -45,7 +55,18 @@ impl Component for TaskSearchBarComponent {
         state: &crate::ui::AppState,
         frame_storage: &crate::ui::FrameLocalStorage,
     ) {
-        self.textbox.render(frame, area, state, frame_storage);
+        let Some(error) = &self.error else {
+            self.textbox.render(frame, area, state, frame_storage);
+            return;
+        };
+
+        // show the parse error next to the query, keeping at least half of the space for the query
+        let error_text = format!(" {error}");
+        let error_width = (error_text.chars().count() as u16).min(area.width / 2);
+        let (textbox_area, error_area) = area.split_last_x(error_width);
+        self.textbox
+            .render(frame, textbox_area, state, frame_storage);
+        frame.render_widget(Paragraph::new(error_text).style(FG_RED), error_area);
     }
 
     fn process_input(
@@ -54,6 +75,18 @@ impl Component for TaskSearchBarComponent {
         state: &mut crate::ui::AppState,
         frame_storage: &crate::ui::FrameLocalStorage,
     ) -> bool {
-        self.textbox.process_input(key, state, frame_storage)
+        if !self.textbox.process_input(key, state, frame_storage) {
+            return false;
+        }
+
+        // keep filtering on the last valid query while the user is still typing
+        match Query::parse(self.textbox.text()) {
+            Ok(query) => {
+                self.query = query;
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+        true
     }
 }
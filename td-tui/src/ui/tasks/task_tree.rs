@@ -0,0 +1,383 @@
+use std::collections::HashSet;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+use td_lib::database::{operations::Operation, Task, TaskId};
+
+use super::{toggle_completed, toggle_timer};
+use crate::{
+    keybinds::*,
+    ui::{
+        component_collection::{CollectionKey, ComponentCollection},
+        constants::*,
+        modal::TextInputModal,
+        AppState, Component, FrameLocalStorage,
+    },
+};
+
+/// Shows the dependency graph as a collapsible tree. The roots are tasks that no other task depends
+/// on, and the children of a task are its dependencies.
+pub struct TaskTree {
+    /// The expanded nodes, as paths from a root task. A task can appear in the tree multiple times
+    /// if multiple tasks depend on it, and each occurrence is expanded separately.
+    expanded: HashSet<Vec<TaskId>>,
+    selected_index: usize,
+    modals: ComponentCollection,
+    rename_task_modal: CollectionKey<TextInputModal>,
+}
+
+/// A visible row in the tree.
+struct TreeRow {
+    /// The path from the root task to this task, including both.
+    path: Vec<TaskId>,
+    has_children: bool,
+    is_expanded: bool,
+}
+
+impl TreeRow {
+    fn task_id(&self) -> &TaskId {
+        self.path.last().expect("path should never be empty")
+    }
+
+    fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+impl TaskTree {
+    const SCROLL_PAGE_UP_DOWN: usize = 32;
+
+    pub fn new() -> Self {
+        let mut modal_collection = ComponentCollection::default();
+        Self {
+            expanded: HashSet::new(),
+            selected_index: 0,
+            rename_task_modal: modal_collection
+                .insert(TextInputModal::new("Rename task".to_string())),
+            modals: modal_collection,
+        }
+    }
+
+    fn is_visible(state: &AppState, task: &Task) -> bool {
+        !state.filter_completed || task.time_completed.is_none()
+    }
+
+    fn sort_tasks(state: &AppState, tasks: &mut [&Task]) {
+        tasks.sort_by_key(|t| t.time_created);
+        if !state.sort_oldest_first {
+            tasks.reverse();
+        }
+    }
+
+    fn get_children<'a>(state: &'a AppState, task_id: &TaskId) -> Vec<&'a Task> {
+        let mut children = state
+            .database
+            .get_dependencies(task_id)
+            .filter(|t| Self::is_visible(state, t))
+            .collect::<Vec<_>>();
+        Self::sort_tasks(state, &mut children);
+        children
+    }
+
+    fn get_rows(&self, state: &AppState) -> Vec<TreeRow> {
+        // a task is a root if no visible task depends on it, so hidden tasks don't hide their
+        // dependencies
+        let mut roots = state
+            .database
+            .get_all_tasks()
+            .filter(|t| Self::is_visible(state, t))
+            .filter(|t| {
+                !state
+                    .database
+                    .get_inverse_dependencies(t.id())
+                    .any(|dependent| Self::is_visible(state, dependent))
+            })
+            .collect::<Vec<_>>();
+        Self::sort_tasks(state, &mut roots);
+
+        let mut rows = vec![];
+        for root in roots {
+            self.push_rows(state, vec![root.id().clone()], &mut rows);
+        }
+        rows
+    }
+
+    fn push_rows(&self, state: &AppState, path: Vec<TaskId>, rows: &mut Vec<TreeRow>) {
+        let task_id = path.last().expect("path should never be empty");
+
+        // databases from before cycle detection may contain cycles, don't follow them
+        let children = Self::get_children(state, task_id)
+            .into_iter()
+            .filter(|child| !path.contains(child.id()))
+            .collect::<Vec<_>>();
+        let is_expanded = !children.is_empty() && self.expanded.contains(&path);
+
+        rows.push(TreeRow {
+            path: path.clone(),
+            has_children: !children.is_empty(),
+            is_expanded,
+        });
+
+        if is_expanded {
+            for child in children {
+                let mut child_path = path.clone();
+                child_path.push(child.id().clone());
+                self.push_rows(state, child_path, rows);
+            }
+        }
+    }
+
+    fn row_to_line(state: &AppState, row: &TreeRow) -> Line<'static> {
+        let task = &state.database[row.task_id()];
+
+        let marker = match (row.has_children, row.is_expanded) {
+            (false, _) => "  ",
+            (true, false) => "▸ ",
+            (true, true) => "▾ ",
+        };
+        let marker_style = if state.database.has_unfinished_dependencies(task.id()) {
+            FG_RED.patch(BOLD)
+        } else {
+            FG_DIM
+        };
+
+        let text_style = if task.time_completed.is_some() {
+            LIST_STYLE.patch(COMPLETED_TASK)
+        } else if task.is_started() {
+            LIST_STYLE.patch(STARTED_TASK)
+        } else {
+            LIST_STYLE
+        };
+
+        let mut spans = vec![
+            Span::raw("  ".repeat(row.depth())),
+            Span::styled(marker, marker_style),
+            Span::styled(task.title.clone(), text_style),
+        ];
+        if task.is_timer_running() {
+            spans.push(Span::styled(" ⏱", FG_GREEN.patch(BOLD)));
+        }
+        for tag in &task.tags {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(tag.clone(), FG_DIM.patch(ITALIC)));
+        }
+
+        spans.into()
+    }
+
+    fn collapse_or_select_parent(&mut self, rows: &[TreeRow]) {
+        let row = &rows[self.selected_index];
+        if row.is_expanded {
+            self.expanded.remove(&row.path);
+        } else if row.depth() > 0 {
+            let parent_path = &row.path[..row.path.len() - 1];
+            if let Some(parent_index) = rows[..self.selected_index]
+                .iter()
+                .rposition(|r| r.path == parent_path)
+            {
+                self.selected_index = parent_index;
+            }
+        }
+    }
+
+    fn expand_or_select_child(&mut self, rows: &[TreeRow]) {
+        let row = &rows[self.selected_index];
+        if row.is_expanded {
+            // the first child is always right below its parent
+            self.selected_index += 1;
+        } else if row.has_children {
+            self.expanded.insert(row.path.clone());
+        }
+    }
+}
+
+impl Component for TaskTree {
+    fn pre_render(&self, global_state: &AppState, frame_storage: &mut FrameLocalStorage) {
+        let rows = self.get_rows(global_state);
+        let selected_row = rows.get(self.selected_index.min(rows.len().saturating_sub(1)));
+        frame_storage.selected_task_id = selected_row.map(|r| r.task_id().clone());
+
+        self.modals.pre_render(global_state, frame_storage);
+
+        let is_task_selected = selected_row.is_some();
+        frame_storage.register_keybind(KEYBIND_CONTROLS_LIST_NAV_EXT, rows.len() >= 2);
+        frame_storage.register_keybind(
+            KEYBIND_TREE_COLLAPSE_EXPAND,
+            selected_row.is_some_and(|r| r.has_children || r.depth() > 0),
+        );
+        frame_storage.register_keybind(KEYBIND_TASK_TOGGLE_TIMER, is_task_selected);
+        frame_storage.register_keybind(KEYBIND_TASK_MARK_DONE, is_task_selected);
+        frame_storage.register_keybind(KEYBIND_TASK_RENAME, is_task_selected);
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        state: &AppState,
+        frame_storage: &FrameLocalStorage,
+    ) {
+        let rows = self.get_rows(state);
+
+        let list_items = rows
+            .iter()
+            .map(|row| ListItem::new(Self::row_to_line(state, row)))
+            .collect::<Vec<_>>();
+        let list = List::new(list_items)
+            .highlight_style(LIST_HIGHLIGHT_STYLE)
+            .style(LIST_STYLE);
+        let mut list_state = ListState::default();
+        list_state.select(
+            (!rows.is_empty()).then_some(self.selected_index.min(rows.len().saturating_sub(1))),
+        );
+        frame.render_stateful_widget(list, area, &mut list_state);
+
+        // if needed, render popups
+        self.modals
+            .render(frame, frame.size(), state, frame_storage);
+    }
+
+    fn process_input(
+        &mut self,
+        key: KeyEvent,
+        state: &mut AppState,
+        frame_storage: &FrameLocalStorage,
+    ) -> bool {
+        if self.modals.process_input(key, state, frame_storage) {
+            return true;
+        }
+
+        let rows = self.get_rows(state);
+        if !rows.is_empty() {
+            self.selected_index = self.selected_index.min(rows.len() - 1);
+        }
+
+        if self.modals[self.rename_task_modal].is_open() {
+            if KEYBIND_MODAL_SUBMIT.is_match(key) {
+                if let (Some(text), Some(row)) = (
+                    self.modals[self.rename_task_modal].close(),
+                    rows.get(self.selected_index),
+                ) {
+                    let description =
+                        format!("Rename task '{}'", state.database[row.task_id()].title);
+                    let id = row.task_id().clone();
+                    _ = state.apply(description, [Operation::Rename { id, title: text }]);
+                }
+                return true;
+            }
+            return false;
+        }
+
+        if let Some(row) = rows.get(self.selected_index) {
+            if KEYBIND_TASK_TOGGLE_TIMER.is_match(key) {
+                toggle_timer(state, row.task_id());
+                return true;
+            } else if KEYBIND_TASK_MARK_DONE.is_match(key) {
+                toggle_completed(state, row.task_id());
+                return true;
+            } else if KEYBIND_TASK_RENAME.is_match(key) {
+                let title = state.database[row.task_id()].title.clone();
+                self.modals[self.rename_task_modal].open_with_text(title);
+                return true;
+            } else if let Some(key) = KEYBIND_TREE_COLLAPSE_EXPAND.get_match(key) {
+                match key {
+                    LeftRightKey::Left => self.collapse_or_select_parent(&rows),
+                    LeftRightKey::Right => self.expand_or_select_child(&rows),
+                }
+                return true;
+            }
+        }
+
+        if let Some(key) = KEYBIND_CONTROLS_LIST_NAV_EXT.get_match(key) {
+            let last_index = rows.len().saturating_sub(1);
+            self.selected_index = match key {
+                UpDownExtendedKey::Up => self.selected_index.saturating_sub(1),
+                UpDownExtendedKey::Down => (self.selected_index + 1).min(last_index),
+                UpDownExtendedKey::PageUp => self
+                    .selected_index
+                    .saturating_sub(Self::SCROLL_PAGE_UP_DOWN),
+                UpDownExtendedKey::PageDown => {
+                    (self.selected_index + Self::SCROLL_PAGE_UP_DOWN).min(last_index)
+                }
+                UpDownExtendedKey::Home => 0,
+                UpDownExtendedKey::End => last_index,
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_task(state: &mut AppState, title: &str) -> TaskId {
+        let task = Task::create_now(title.into());
+        let id = task.id().clone();
+        state.database.modify("Add task", |db| db.add_task(task));
+        id
+    }
+
+    #[test]
+    fn shared_dependencies_are_expanded_separately() {
+        let mut state = AppState::default();
+        let a = create_task(&mut state, "a");
+        let b = create_task(&mut state, "b");
+        let shared = create_task(&mut state, "shared");
+        let leaf = create_task(&mut state, "leaf");
+        state.database.modify("Add dependencies", |db| {
+            db.add_dependency(&a, &shared).unwrap();
+            db.add_dependency(&b, &shared).unwrap();
+            db.add_dependency(&shared, &leaf).unwrap();
+        });
+
+        let mut tree = TaskTree::new();
+        let rows = tree.get_rows(&state);
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|r| r.has_children && !r.is_expanded));
+
+        tree.expanded.insert(vec![a.clone()]);
+        tree.expanded.insert(vec![a.clone(), shared.clone()]);
+        tree.expanded.insert(vec![b.clone()]);
+        let paths = tree
+            .get_rows(&state)
+            .into_iter()
+            .map(|r| r.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                vec![b.clone()],
+                vec![b.clone(), shared.clone()],
+                vec![a.clone()],
+                vec![a.clone(), shared.clone()],
+                vec![a.clone(), shared.clone(), leaf.clone()],
+            ]
+        );
+    }
+
+    #[test]
+    fn completed_dependents_do_not_hide_tasks() {
+        let mut state = AppState {
+            filter_completed: true,
+            ..Default::default()
+        };
+        let done = create_task(&mut state, "done");
+        let open = create_task(&mut state, "open");
+        state.database.modify("Complete task", |db| {
+            db.add_dependency(&done, &open).unwrap();
+            db[&done].time_completed = Some(db[&done].time_created);
+        });
+
+        let rows = TaskTree::new().get_rows(&state);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].task_id(), &open);
+    }
+}
@@ -10,6 +10,7 @@ use ratatui::{
     layout::Rect,
     text::{Line, Span},
 };
+use td_lib::time::{Date, OffsetDateTime};
 use tui_input::InputRequest;
 
 pub trait RectExt {
@@ -37,6 +38,7 @@ pub trait RectExt {
     where
         Self: Sized;
 
+    #[allow(dead_code)]
     fn slice_x(self, range: impl RangeBounds<u16>) -> Self;
     fn slice_y(self, range: impl RangeBounds<u16>) -> Self;
 
@@ -132,6 +134,16 @@ impl RectExt for Rect {
     }
 }
 
+/// Gets the current time in the local timezone, or in UTC if the local timezone is unknown.
+pub fn now() -> OffsetDateTime {
+    OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc())
+}
+
+/// Gets the current date in the local timezone, or in UTC if the local timezone is unknown.
+pub fn local_today() -> Date {
+    now().date()
+}
+
 pub fn wrap_text(text: &str, width: u16) -> Vec<String> {
     // see process at https://docs.rs/textwrap/latest/textwrap/core/index.html
     // we need to do this manually because we want to retain whitespace at the end of lines
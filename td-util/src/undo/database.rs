@@ -0,0 +1,293 @@
+//! Undo support for the task database, by keeping track of which tasks and dependencies changed.
+
+use std::{collections::HashSet, mem::size_of};
+
+use serde::{Deserialize, Serialize};
+use td_lib::database::{operations::Operation, sessions::WorkSession, Database, Task, TaskId};
+
+use super::{Diff, EstimateSize};
+
+/// A rough guess of how much memory a dependency uses in the task graph.
+const DEPENDENCY_SIZE: usize = 16;
+
+/// The changes between 2 versions of a [`Database`]. See [`Diff`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseDelta {
+    added_tasks: Vec<Task>,
+    /// The removed tasks, as they were before being removed.
+    removed_tasks: Vec<Task>,
+    /// The changed tasks, as they were before and after the change.
+    changed_tasks: Vec<(Task, Task)>,
+    /// The added dependencies, from the dependent task to its dependency.
+    added_dependencies: Vec<(TaskId, TaskId)>,
+    /// The removed dependencies, from the dependent task to its dependency.
+    removed_dependencies: Vec<(TaskId, TaskId)>,
+}
+
+impl DatabaseDelta {
+    /// Checks whether this delta does not change anything.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_tasks.is_empty()
+            && self.removed_tasks.is_empty()
+            && self.changed_tasks.is_empty()
+            && self.added_dependencies.is_empty()
+            && self.removed_dependencies.is_empty()
+    }
+
+    fn inverse(self) -> Self {
+        Self {
+            added_tasks: self.removed_tasks,
+            removed_tasks: self.added_tasks,
+            changed_tasks: self
+                .changed_tasks
+                .into_iter()
+                .map(|(before, after)| (after, before))
+                .collect(),
+            added_dependencies: self.removed_dependencies,
+            removed_dependencies: self.added_dependencies,
+        }
+    }
+}
+
+impl From<DatabaseDelta> for Operation {
+    /// Creates an operation that makes the same changes as the delta, so they can be logged.
+    fn from(delta: DatabaseDelta) -> Self {
+        Self::Restore {
+            tasks: delta
+                .added_tasks
+                .into_iter()
+                .chain(delta.changed_tasks.into_iter().map(|(_, after)| after))
+                .collect(),
+            removed_tasks: delta
+                .removed_tasks
+                .iter()
+                .map(|task| task.id().clone())
+                .collect(),
+            added_dependencies: delta.added_dependencies,
+            removed_dependencies: delta.removed_dependencies,
+        }
+    }
+}
+
+/// Gets all dependencies in the database, in the order they are stored.
+fn get_all_dependencies(database: &Database) -> Vec<(TaskId, TaskId)> {
+    database
+        .get_all_tasks()
+        .flat_map(|task| {
+            database
+                .get_dependencies(task.id())
+                .map(|dependency| (task.id().clone(), dependency.id().clone()))
+        })
+        .collect()
+}
+
+impl Diff for Database {
+    type Delta = DatabaseDelta;
+
+    fn diff(from: &Self, to: &Self) -> Self::Delta {
+        let mut delta = DatabaseDelta::default();
+        for task in from.get_all_tasks() {
+            match to.get(task.id()) {
+                None => delta.removed_tasks.push(task.clone()),
+                Some(new_task) if new_task != task => {
+                    delta.changed_tasks.push((task.clone(), new_task.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        delta.added_tasks = to
+            .get_all_tasks()
+            .filter(|task| !from.contains_task(task.id()))
+            .cloned()
+            .collect();
+
+        let from_dependencies = get_all_dependencies(from);
+        let to_dependencies = get_all_dependencies(to);
+        let from_set = from_dependencies.iter().collect::<HashSet<_>>();
+        let to_set = to_dependencies.iter().collect::<HashSet<_>>();
+        delta.removed_dependencies = from_dependencies
+            .iter()
+            .filter(|dependency| !to_set.contains(dependency))
+            .cloned()
+            .collect();
+        delta.added_dependencies = to_dependencies
+            .iter()
+            .filter(|dependency| !from_set.contains(dependency))
+            .cloned()
+            .collect();
+
+        delta
+    }
+
+    fn apply_delta(&mut self, delta: Self::Delta) -> Self::Delta {
+        for (from, to) in &delta.removed_dependencies {
+            self.remove_dependency(from, to);
+        }
+        for task in &delta.removed_tasks {
+            self.remove_task(task.id());
+        }
+        for task in &delta.added_tasks {
+            self.add_task(task.clone());
+        }
+        for (_, task) in &delta.changed_tasks {
+            self[task.id()] = task.clone();
+        }
+        // the previous state may have contained dependency cycles, which have to be restored too
+        for (from, to) in &delta.added_dependencies {
+            self.restore_dependency(from, to);
+        }
+
+        delta.inverse()
+    }
+}
+
+impl EstimateSize for Task {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>()
+            + self.id().to_string().len()
+            + self.title.capacity()
+            + self.description.capacity()
+            + self.sessions.capacity() * size_of::<WorkSession>()
+            + self
+                .tags
+                .iter()
+                .map(|tag| size_of::<String>() + tag.capacity())
+                .sum::<usize>()
+    }
+}
+
+impl EstimateSize for Database {
+    fn estimated_size(&self) -> usize {
+        self.get_all_tasks()
+            .map(|task| {
+                let dependency_count = self.get_dependencies(task.id()).count();
+                task.estimated_size() + dependency_count * DEPENDENCY_SIZE
+            })
+            .sum()
+    }
+}
+
+impl EstimateSize for DatabaseDelta {
+    fn estimated_size(&self) -> usize {
+        let dependency_count = self.added_dependencies.len() + self.removed_dependencies.len();
+        size_of::<Self>()
+            + self
+                .added_tasks
+                .iter()
+                .chain(&self.removed_tasks)
+                .chain(self.changed_tasks.iter().flat_map(|(a, b)| [a, b]))
+                .map(EstimateSize::estimated_size)
+                .sum::<usize>()
+            + dependency_count * size_of::<(TaskId, TaskId)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::undo::{Deltas, UndoWrapper};
+
+    fn dependencies(database: &Database) -> Vec<(String, String)> {
+        let titles = |(from, to): (TaskId, TaskId)| {
+            (database[&from].title.clone(), database[&to].title.clone())
+        };
+        get_all_dependencies(database)
+            .into_iter()
+            .map(titles)
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_database_changes() {
+        let mut database = Database::default();
+        let mut ids = vec![];
+        for title in ["a", "b", "c"] {
+            let task = Task::create_now(title.into());
+            ids.push(task.id().clone());
+            database.add_task(task);
+        }
+        database.add_dependency(&ids[0], &ids[1]).unwrap();
+
+        let mut undo = UndoWrapper::<_, Deltas>::with_storage(database);
+        undo.modify("Change tasks", |db| {
+            db.remove_task(&ids[1]);
+            db[&ids[2]].title = "renamed".into();
+            db.add_dependency(&ids[2], &ids[0]).unwrap();
+            db.add_task(Task::create_now("d".into()));
+        });
+        let titles = |db: &Database| {
+            db.get_all_tasks()
+                .map(|t| t.title.clone())
+                .collect::<Vec<_>>()
+        };
+        // the new task takes the place of the removed one
+        assert_eq!(titles(&undo), ["a", "d", "renamed"]);
+
+        undo.undo();
+        assert_eq!(titles(&undo), ["a", "b", "c"]);
+        assert_eq!(dependencies(&undo), [("a".into(), "b".into())]);
+
+        undo.redo();
+        assert_eq!(titles(&undo), ["a", "d", "renamed"]);
+        assert_eq!(dependencies(&undo), [("renamed".into(), "a".into())]);
+    }
+
+    #[test]
+    fn undo_restores_dependency_cycle() {
+        let mut database = Database::default();
+        let mut ids = vec![];
+        for title in ["a", "b", "c"] {
+            let task = Task::create_now(title.into());
+            ids.push(task.id().clone());
+            database.add_task(task);
+        }
+        // a cycle like the ones in databases from before cycles were rejected
+        database.restore_dependency(&ids[0], &ids[1]);
+        database.restore_dependency(&ids[1], &ids[2]);
+        database.restore_dependency(&ids[2], &ids[0]);
+        let before = dependencies(&database);
+
+        let mut undo = UndoWrapper::<_, Deltas>::with_storage(database);
+        undo.modify("Remove task", |db| db.remove_task(&ids[1]));
+        assert_eq!(dependencies(&undo), [("c".into(), "a".into())]);
+
+        undo.undo();
+        assert_eq!(dependencies(&undo), before);
+        undo.redo();
+        undo.undo();
+        assert_eq!(dependencies(&undo), before);
+    }
+
+    #[test]
+    fn delta_as_operation_restores_state() {
+        let mut before = Database::default();
+        let mut ids = vec![];
+        for title in ["a", "b", "c"] {
+            let task = Task::create_now(title.into());
+            ids.push(task.id().clone());
+            before.add_task(task);
+        }
+        before.add_dependency(&ids[0], &ids[1]).unwrap();
+
+        let mut after = before.clone();
+        after.remove_task(&ids[1]);
+        after[&ids[0]].title = "renamed".into();
+        after.add_task(Task::create_now("d".into()));
+        after.add_dependency(&ids[0], &ids[2]).unwrap();
+
+        let operation = Operation::from(Database::diff(&after, &before));
+        assert_eq!(after.apply(&operation), Ok(true));
+        assert!(Database::diff(&after, &before).is_empty());
+        assert_eq!(after.apply(&operation), Ok(false));
+    }
+
+    #[test]
+    fn unchanged_database_has_empty_delta() {
+        let mut database = Database::default();
+        database.add_task(Task::create_now("a".into()));
+
+        assert!(Database::diff(&database, &database.clone()).is_empty());
+        assert!(database.estimated_size() > size_of::<Task>());
+    }
+}
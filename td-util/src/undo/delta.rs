@@ -0,0 +1,32 @@
+//! Storing the differences between states instead of full copies of them.
+
+use super::UndoStorage;
+
+/// A state that can compute the differences between 2 versions of itself. See [`Deltas`].
+pub trait Diff {
+    /// The changes that turn one version of the state into another.
+    type Delta;
+
+    /// Computes the delta that turns `from` into `to`.
+    fn diff(from: &Self, to: &Self) -> Self::Delta;
+
+    /// Applies a delta to the version of the state it was computed from. Returns the delta that
+    /// reverts this.
+    fn apply_delta(&mut self, delta: Self::Delta) -> Self::Delta;
+}
+
+/// Stores the differences between states instead of a full copy of every state. The state is
+/// still cloned when it is modified to compute the difference, but this copy is not kept around.
+pub struct Deltas;
+
+impl<T: Diff> UndoStorage<T> for Deltas {
+    type Entry = T::Delta;
+
+    fn record(old: T, new: &T) -> Self::Entry {
+        T::diff(new, &old)
+    }
+
+    fn apply(state: &mut T, entry: Self::Entry) -> Self::Entry {
+        state.apply_delta(entry)
+    }
+}
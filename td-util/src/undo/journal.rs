@@ -0,0 +1,178 @@
+//! Storing the recent history of an [`UndoWrapper`], so it can be restored after restarting.
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Node, StateId, UndoStorage, UndoWrapper};
+
+/// The current state of an [`UndoWrapper`] and the states leading up to it, without the state
+/// itself. Created using [`UndoWrapper::to_journal`] and restored using
+/// [`UndoWrapper::restore_journal`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoJournal<E> {
+    /// The states leading up to the current one, oldest first.
+    states: Vec<JournalState<E>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalState<E> {
+    id: StateId,
+    description: String,
+    created: SystemTime,
+    /// Moves from this state to the previous one. `None` for the oldest state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<E>,
+}
+
+impl<E> UndoJournal<E> {
+    /// Gets how many times the state can be reverted after restoring this journal.
+    #[must_use]
+    pub fn undo_count(&self) -> usize {
+        self.states.len().saturating_sub(1)
+    }
+
+    /// Checks whether every state can be reached from the current one. States must be in the order
+    /// they were created, and every state except the oldest must be able to move back.
+    fn is_valid(&self) -> bool {
+        let in_order = self.states.windows(2).all(|pair| pair[0].id < pair[1].id);
+        let has_entries = self
+            .states
+            .iter()
+            .skip(1)
+            .all(|state| state.entry.is_some());
+        !self.states.is_empty() && in_order && has_entries
+    }
+}
+
+impl<T: Clone, S: UndoStorage<T>> UndoWrapper<T, S>
+where
+    S::Entry: Clone,
+{
+    /// Creates a journal of the current state and at most `max_steps` states before it. Undone
+    /// states and other branches are not included.
+    #[must_use]
+    pub fn to_journal(&self, max_steps: usize) -> UndoJournal<S::Entry> {
+        let mut states: Vec<_> = self
+            .ancestors(self.current_id)
+            .take(max_steps.saturating_add(1))
+            .map(|id| {
+                let node = self.node(id);
+                JournalState {
+                    id,
+                    description: node.description.clone(),
+                    created: node.created,
+                    entry: node.entry.as_ref().map(|entry| entry.entry.clone()),
+                }
+            })
+            .collect();
+        states.reverse();
+
+        // the oldest state can not go back any further
+        if let Some(oldest) = states.first_mut() {
+            oldest.entry = None;
+        }
+
+        UndoJournal { states }
+    }
+}
+
+impl<T: Clone, S: UndoStorage<T>> UndoWrapper<T, S> {
+    /// Replaces the history with the one in a journal, so the states in it can be undone. The
+    /// current state must be the same one the journal was created from.
+    ///
+    /// Returns `false` without changing the history if the journal is invalid, or if a
+    /// [transaction](Self::transaction) is in progress.
+    pub fn restore_journal(&mut self, journal: UndoJournal<S::Entry>) -> bool {
+        if self.in_transaction() || !journal.is_valid() {
+            return false;
+        }
+
+        self.nodes.clear();
+        self.clean_id = None;
+        self.history_bytes = 0;
+
+        let mut parent: Option<StateId> = None;
+        for state in journal.states {
+            let entry = state.entry.map(|entry| self.create_entry(entry));
+            self.history_bytes += entry.as_ref().map_or(0, |entry| entry.size);
+            if let Some(parent) = parent {
+                let parent = self.node_mut(parent);
+                parent.children.push(state.id);
+                parent.redo_child = Some(state.id);
+            }
+
+            let node = Node {
+                parent,
+                description: state.description,
+                entry,
+                children: vec![],
+                redo_child: None,
+                created: state.created,
+            };
+            self.nodes.insert(state.id, node);
+            parent = Some(state.id);
+        }
+
+        let current_id = parent.expect("journal should not be empty");
+        self.current_id = current_id;
+        self.next_id = current_id.0 + 1;
+        self.trim_history();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_journal() {
+        let mut undo = UndoWrapper::new(0);
+        undo.modify("one", |x| *x = 1);
+        undo.modify("two", |x| *x = 2);
+        undo.modify("three", |x| *x = 3);
+        undo.undo();
+
+        let journal = undo.to_journal(1);
+        assert_eq!(journal.undo_count(), 1);
+        let json = serde_json::to_string(&journal).unwrap();
+
+        let mut restored = UndoWrapper::new(2);
+        assert!(restored.restore_journal(serde_json::from_str(&json).unwrap()));
+        restored.mark_clean();
+        assert_eq!(restored.undo_description(), Some("two"));
+        assert_eq!(restored.redo_count(), 0);
+
+        assert!(restored.undo());
+        assert_eq!(*restored, 1);
+        assert!(!restored.undo());
+        assert!(restored.is_dirty());
+
+        restored.modify("four", |x| *x = 4);
+        assert!(restored.current_id() > undo.current_id());
+    }
+
+    #[test]
+    fn restored_journal_is_trimmed() {
+        let mut undo = UndoWrapper::new(0);
+        undo.modify("add", |x| *x += 5);
+        undo.modify("double", |x| *x *= 2);
+
+        let mut restored = UndoWrapper::new(10).with_max_steps(1);
+        assert!(restored.restore_journal(undo.to_journal(10)));
+        assert_eq!(restored.undo_count(), 1);
+        assert!(restored.undo());
+        assert_eq!(*restored, 5);
+    }
+
+    #[test]
+    fn invalid_journal_is_ignored() {
+        let mut undo = UndoWrapper::new(0);
+        undo.modify("one", |x| *x = 1);
+
+        let empty = UndoJournal { states: vec![] };
+        assert!(!undo.restore_journal(empty));
+        assert_eq!(undo.undo_description(), Some("one"));
+    }
+}
@@ -0,0 +1,1057 @@
+//! Provides generic undo functionality on an arbitrary state object.
+
+mod database;
+mod delta;
+mod journal;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    ops::Deref,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    database::DatabaseDelta,
+    delta::{Deltas, Diff},
+    journal::UndoJournal,
+};
+
+/// A wrapper for a state, allowing rolling back changes using an undo-redo system.
+///
+/// The history is stored as a tree of states. Normally, modifying the state after undoing discards
+/// the undone states, like a linear undo stack. In [undo tree mode](Self::with_undo_tree) they are
+/// kept as a separate branch instead, and every state can be returned to using [`Self::go_to`].
+///
+/// Every modification has a description of what it changed, which can be shown to the user before
+/// undoing it. Several modifications can be grouped into one step using [`Self::transaction`].
+///
+/// How previous states are kept around is decided by `S`. By default, a full copy of every state
+/// is kept (see [`Snapshots`]), while [`Deltas`] only keeps the differences between states.
+///
+/// The history is unbounded unless limited using [`Self::with_max_steps`] or
+/// [`Self::with_max_bytes`], in which case the oldest states are dropped.
+pub struct UndoWrapper<T: Clone, S: UndoStorage<T> = Snapshots> {
+    state: T,
+    current_id: StateId,
+    /// Every state in the history, including the current one, in the order they were created.
+    nodes: BTreeMap<StateId, Node<S::Entry>>,
+    next_id: u64,
+    clean_id: Option<StateId>,
+    keep_branches: bool,
+    transaction: Option<Transaction<T>>,
+    max_steps: Option<usize>,
+    max_bytes: Option<usize>,
+    /// Estimates the size of an entry. Only set if the history is limited in bytes.
+    entry_size: Option<fn(&S::Entry) -> usize>,
+    /// The estimated size of all entries in the history.
+    history_bytes: usize,
+}
+
+/// Identifies a state in the history of an [`UndoWrapper`]. States are numbered in the order they
+/// were created, so a newer state has a higher id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StateId(u64);
+
+/// A state in the history of an [`UndoWrapper`]. See [`UndoWrapper::history`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryState<'a> {
+    /// The id of this state.
+    pub id: StateId,
+    /// A description of the modification that created this state. Empty for the initial state.
+    pub description: &'a str,
+    /// The state this one was created from, or `None` for the oldest state in the history.
+    pub parent: Option<StateId>,
+    /// When this state was created.
+    pub created: SystemTime,
+}
+
+struct Node<E> {
+    parent: Option<StateId>,
+    description: String,
+    /// Moves between this state and its parent. It moves towards the parent if the current state
+    /// is this state or one of its descendants, and towards this state otherwise. `None` for the
+    /// oldest state.
+    entry: Option<HistoryEntry<E>>,
+    children: Vec<StateId>,
+    /// The child that [`UndoWrapper::redo`] moves to, which is the one visited most recently.
+    redo_child: Option<StateId>,
+    created: SystemTime,
+}
+
+/// Modifications that are being grouped into one step. See [`UndoWrapper::transaction`].
+struct Transaction<T> {
+    description: String,
+    /// The state before the transaction started.
+    old_state: T,
+    changed: bool,
+}
+
+struct HistoryEntry<E> {
+    entry: E,
+    size: usize,
+}
+
+/// How an [`UndoWrapper`] stores the states it can go back and forth to.
+pub trait UndoStorage<T> {
+    /// A step in the history, which turns the current state into an adjacent one.
+    type Entry;
+
+    /// Creates the entry that goes back from `new` to `old`, after `old` was modified into `new`.
+    fn record(old: T, new: &T) -> Self::Entry;
+
+    /// Applies an entry to the state. Returns the entry that goes back to the state before.
+    fn apply(state: &mut T, entry: Self::Entry) -> Self::Entry;
+}
+
+/// Stores a full copy of every state. This is the default storage of an [`UndoWrapper`].
+pub struct Snapshots;
+
+/// Estimates how much memory a value uses. See [`UndoWrapper::with_max_bytes`].
+pub trait EstimateSize {
+    /// Gets the approximate amount of bytes used by this value, including heap allocations.
+    fn estimated_size(&self) -> usize;
+}
+
+impl<T> UndoStorage<T> for Snapshots {
+    type Entry = T;
+
+    fn record(old: T, _new: &T) -> Self::Entry {
+        old
+    }
+
+    fn apply(state: &mut T, entry: Self::Entry) -> Self::Entry {
+        std::mem::replace(state, entry)
+    }
+}
+
+impl Display for StateId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl<T: Clone> UndoWrapper<T> {
+    /// Create a new instance with the given state as the current (and only) state.
+    pub fn new(initial_state: T) -> Self {
+        Self::with_storage(initial_state)
+    }
+}
+
+impl<T: Clone, S: UndoStorage<T>> UndoWrapper<T, S> {
+    /// Like [`Self::new`], but previous states are stored using `S`, such as [`Deltas`].
+    pub fn with_storage(initial_state: T) -> Self {
+        let root = Node {
+            parent: None,
+            description: String::new(),
+            entry: None,
+            children: vec![],
+            redo_child: None,
+            created: SystemTime::now(),
+        };
+
+        Self {
+            state: initial_state,
+            current_id: StateId(0),
+            nodes: BTreeMap::from([(StateId(0), root)]),
+            next_id: 1,
+            clean_id: None,
+            keep_branches: false,
+            transaction: None,
+            max_steps: None,
+            max_bytes: None,
+            entry_size: None,
+            history_bytes: 0,
+        }
+    }
+
+    /// Keeps every branch of the history. Modifying the state after undoing no longer discards the
+    /// undone states, so they can still be returned to using [`Self::go_to`].
+    #[must_use]
+    pub fn with_undo_tree(mut self) -> Self {
+        self.keep_branches = true;
+        self
+    }
+
+    /// Limits how many states are kept besides the current one. Once there are more, the oldest
+    /// states are dropped.
+    #[must_use]
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self.trim_history();
+        self
+    }
+
+    /// Limits the estimated memory used by the history. Once it uses more, the oldest states are
+    /// dropped. The most recent step is always kept, even if it is larger than the limit.
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self
+    where
+        S::Entry: EstimateSize,
+    {
+        self.max_bytes = Some(max_bytes);
+        self.entry_size = Some(<S::Entry as EstimateSize>::estimated_size);
+        for entry in self
+            .nodes
+            .values_mut()
+            .filter_map(|node| node.entry.as_mut())
+        {
+            entry.size = entry.entry.estimated_size();
+        }
+        self.history_bytes = self
+            .nodes
+            .values()
+            .filter_map(|node| node.entry.as_ref())
+            .map(|entry| entry.size)
+            .sum();
+        self.trim_history();
+        self
+    }
+
+    /// Gets a reference to the current state.
+    #[must_use]
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+
+    /// Gets a mutable reference to the current state. Doing this will create a new copy of the
+    /// state that gets mutated, allowing calling undo to roll back to the previous state later.
+    ///
+    /// The description says what the modification does, such as `Rename task 'X'`. Inside a
+    /// transaction, the description of the transaction is used instead.
+    pub fn modify<F: FnOnce(&mut T)>(&mut self, description: impl Into<String>, func: F) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.changed = true;
+            func(&mut self.state);
+            return;
+        }
+
+        let old_state = self.state.clone();
+        func(&mut self.state);
+        self.push_state(description.into(), old_state);
+    }
+
+    /// Like [`Self::modify`], but the modification can fail. If `func` returns an error, the
+    /// modified copy is discarded and no new undo state is created.
+    pub fn try_modify<R, E, F: FnOnce(&mut T) -> Result<R, E>>(
+        &mut self,
+        description: impl Into<String>,
+        func: F,
+    ) -> Result<R, E> {
+        let mut new_state = self.state.clone();
+        let ret = func(&mut new_state)?;
+
+        let old_state = std::mem::replace(&mut self.state, new_state);
+        match &mut self.transaction {
+            Some(transaction) => transaction.changed = true,
+            None => self.push_state(description.into(), old_state),
+        }
+        Ok(ret)
+    }
+
+    /// Groups all modifications made by `func` into a single step with the given description, so
+    /// they are undone together. If nothing is modified, no step is created. Transactions inside
+    /// a transaction become part of the outer one.
+    ///
+    /// Undoing, redoing or moving to another state inside a transaction does nothing.
+    pub fn transaction<R, F: FnOnce(&mut Self) -> R>(
+        &mut self,
+        description: impl Into<String>,
+        func: F,
+    ) -> R {
+        if self.transaction.is_some() {
+            return func(self);
+        }
+
+        self.transaction = Some(Transaction {
+            description: description.into(),
+            old_state: self.state.clone(),
+            changed: false,
+        });
+        let ret = func(self);
+
+        let transaction = self
+            .transaction
+            .take()
+            .expect("transaction should still be open");
+        if transaction.changed {
+            self.push_state(transaction.description, transaction.old_state);
+        }
+        ret
+    }
+
+    /// Returns whether a transaction is open. See [`Self::transaction`].
+    #[must_use]
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Adds the current state to the history as a child of the previous one.
+    fn push_state(&mut self, description: String, old_state: T) {
+        if !self.keep_branches {
+            self.clear_redo_states();
+        }
+
+        let entry = self.create_entry(S::record(old_state, &self.state));
+        self.history_bytes += entry.size;
+
+        let id = StateId(self.next_id);
+        self.next_id += 1;
+        let parent = self.node_mut(self.current_id);
+        parent.children.push(id);
+        parent.redo_child = Some(id);
+        self.nodes.insert(
+            id,
+            Node {
+                parent: Some(self.current_id),
+                description,
+                entry: Some(entry),
+                children: vec![],
+                redo_child: None,
+                created: SystemTime::now(),
+            },
+        );
+        self.current_id = id;
+
+        self.trim_history();
+    }
+
+    fn create_entry(&self, entry: S::Entry) -> HistoryEntry<S::Entry> {
+        let size = self.entry_size.map_or(0, |entry_size| entry_size(&entry));
+        HistoryEntry { entry, size }
+    }
+
+    fn node(&self, id: StateId) -> &Node<S::Entry> {
+        &self.nodes[&id]
+    }
+
+    fn node_mut(&mut self, id: StateId) -> &mut Node<S::Entry> {
+        self.nodes
+            .get_mut(&id)
+            .expect("state should be in the history")
+    }
+
+    /// Removes every state that was undone from the current one.
+    fn clear_redo_states(&mut self) {
+        let current = self.node_mut(self.current_id);
+        current.redo_child = None;
+        let mut removed = std::mem::take(&mut current.children);
+
+        while let Some(id) = removed.pop() {
+            let node = self
+                .nodes
+                .remove(&id)
+                .expect("state should be in the history");
+            self.history_bytes -= node.entry.map_or(0, |entry| entry.size);
+            removed.extend(node.children);
+            if self.clean_id == Some(id) {
+                self.clean_id = None;
+            }
+        }
+    }
+
+    /// Moves the current state across the edge between the given state and its parent.
+    fn cross_edge(&mut self, id: StateId) {
+        let entry = self
+            .node_mut(id)
+            .entry
+            .take()
+            .expect("only the oldest state has no entry");
+        self.history_bytes -= entry.size;
+
+        let entry = S::apply(&mut self.state, entry.entry);
+        let entry = self.create_entry(entry);
+        self.history_bytes += entry.size;
+        self.node_mut(id).entry = Some(entry);
+    }
+
+    /// Drops the oldest states until the history fits within its limits.
+    fn trim_history(&mut self) {
+        while self.exceeds_limits() {
+            let Some(id) = self.oldest_removable_state() else {
+                break;
+            };
+            self.remove_state(id);
+        }
+    }
+
+    fn exceeds_limits(&self) -> bool {
+        let steps = self.nodes.len() - 1;
+        let too_large = self
+            .max_bytes
+            .is_some_and(|max_bytes| self.history_bytes > max_bytes);
+        self.max_steps.is_some_and(|max_steps| steps > max_steps) || (steps > 1 && too_large)
+    }
+
+    /// Finds the oldest state that can be dropped without splitting up the history. This is either
+    /// the oldest state if it has a single child, or a state at the end of a branch. The current
+    /// state is never dropped.
+    fn oldest_removable_state(&self) -> Option<StateId> {
+        self.nodes
+            .iter()
+            .find(|(&id, node)| {
+                id != self.current_id
+                    && (node.children.is_empty()
+                        || (node.parent.is_none() && node.children.len() == 1))
+            })
+            .map(|(&id, _)| id)
+    }
+
+    fn remove_state(&mut self, id: StateId) {
+        let node = self
+            .nodes
+            .remove(&id)
+            .expect("state should be in the history");
+        self.history_bytes -= node.entry.map_or(0, |entry| entry.size);
+
+        if let Some(parent) = node.parent {
+            let parent = self.node_mut(parent);
+            parent.children.retain(|&child| child != id);
+            if parent.redo_child == Some(id) {
+                parent.redo_child = None;
+            }
+        } else if let Some(&child) = node.children.first() {
+            // the child becomes the oldest state
+            let child = self.node_mut(child);
+            child.parent = None;
+            let entry = child.entry.take();
+            self.history_bytes -= entry.map_or(0, |entry| entry.size);
+        }
+
+        // the clean state can not be reached anymore if it was dropped
+        if self.clean_id == Some(id) {
+            self.clean_id = None;
+        }
+    }
+
+    /// Sets the current state back one state, if possible. Returns `true` if the current state has
+    /// changed.
+    pub fn undo(&mut self) -> bool {
+        if self.in_transaction() {
+            return false;
+        }
+
+        let current_id = self.current_id;
+        let Some(parent) = self.node(current_id).parent else {
+            return false;
+        };
+
+        self.cross_edge(current_id);
+        self.node_mut(parent).redo_child = Some(current_id);
+        self.current_id = parent;
+        true
+    }
+
+    /// Gets the description of the modification that [`Self::undo`] reverts, if there is one.
+    #[must_use]
+    pub fn undo_description(&self) -> Option<&str> {
+        let node = self.node(self.current_id);
+        node.parent.map(|_| node.description.as_str())
+    }
+
+    /// Returns how many times the state can be reverted.
+    #[must_use]
+    pub fn undo_count(&self) -> usize {
+        self.ancestors(self.current_id).count() - 1
+    }
+
+    /// Forwards the state one stage after calling [`Self::undo`]. This will only work right before
+    /// an undo, modifying the current state using [`Self::modify`] will clear the redo queue. In
+    /// [undo tree mode](Self::with_undo_tree), this moves to the most recently visited branch.
+    pub fn redo(&mut self) -> bool {
+        if self.in_transaction() {
+            return false;
+        }
+
+        let Some(child) = self.node(self.current_id).redo_child else {
+            return false;
+        };
+
+        self.cross_edge(child);
+        self.current_id = child;
+        true
+    }
+
+    /// Gets the description of the modification that [`Self::redo`] applies again, if there is one.
+    #[must_use]
+    pub fn redo_description(&self) -> Option<&str> {
+        let child = self.node(self.current_id).redo_child?;
+        Some(&self.node(child).description)
+    }
+
+    /// Returns how many times the state can be forwarded.
+    #[must_use]
+    pub fn redo_count(&self) -> usize {
+        std::iter::successors(self.node(self.current_id).redo_child, |&id| {
+            self.node(id).redo_child
+        })
+        .count()
+    }
+
+    /// Marks the current state as the "clean" state. This can be used to keep track of which state
+    /// is consistent with an externally saved one, such as the version "on disk".
+    pub fn mark_clean(&mut self) {
+        self.clean_id = Some(self.current_id);
+    }
+
+    /// Returns whether the current state is "dirty". See [`Self::mark_clean`].
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.clean_id != Some(self.current_id)
+    }
+
+    /// Gets the id of the current state.
+    #[must_use]
+    pub fn current_id(&self) -> StateId {
+        self.current_id
+    }
+
+    /// Gets the id of the state that was marked clean, if it is still in the history. See
+    /// [`Self::mark_clean`].
+    #[must_use]
+    pub fn clean_id(&self) -> Option<StateId> {
+        self.clean_id
+    }
+
+    /// Gets every state in the history, including the current one, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = HistoryState<'_>> {
+        self.nodes.iter().map(|(&id, node)| HistoryState {
+            id,
+            description: &node.description,
+            parent: node.parent,
+            created: node.created,
+        })
+    }
+
+    /// Gets the given state and all the states it was created from, newest first.
+    pub fn ancestors(&self, id: StateId) -> impl Iterator<Item = StateId> + '_ {
+        std::iter::successors(self.nodes.contains_key(&id).then_some(id), |&id| {
+            self.node(id).parent
+        })
+    }
+
+    /// Gets the last state of every branch in the history, oldest first. Unless in
+    /// [undo tree mode](Self::with_undo_tree), there is only one branch.
+    #[must_use]
+    pub fn branches(&self) -> Vec<StateId> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| node.children.is_empty())
+            .map(|(&id, _)| id)
+            .collect()
+    }
+
+    /// Moves to the given state, undoing and redoing changes as needed. Returns `true` if the
+    /// current state has changed, which is not the case if the state is not in the history.
+    ///
+    /// Afterwards, [`Self::redo`] follows the path that was taken to the given state.
+    pub fn go_to(&mut self, target: StateId) -> bool {
+        if target == self.current_id || !self.nodes.contains_key(&target) || self.in_transaction() {
+            return false;
+        }
+
+        let target_path = self.ancestors(target).collect::<Vec<_>>();
+        let target_ancestors = target_path.iter().copied().collect::<HashSet<_>>();
+        while !target_ancestors.contains(&self.current_id) {
+            self.undo();
+        }
+
+        let common_ancestor = target_path
+            .iter()
+            .position(|&id| id == self.current_id)
+            .expect("the current state should be an ancestor of the target");
+        for &id in target_path[..common_ancestor].iter().rev() {
+            self.node_mut(self.current_id).redo_child = Some(id);
+            self.redo();
+        }
+        true
+    }
+
+    /// Gets the state that was most recently created at or before the given time. If every state
+    /// is newer, the oldest state is returned.
+    #[must_use]
+    pub fn state_at(&self, time: SystemTime) -> StateId {
+        let mut states = self.nodes.iter();
+        let (&oldest, _) = states.next().expect("history should not be empty");
+        states
+            .filter(|(_, node)| node.created <= time)
+            .map(|(&id, _)| id)
+            .last()
+            .unwrap_or(oldest)
+    }
+
+    /// Moves to the state that was most recently created at the given time, such as 10 minutes
+    /// ago. See [`Self::state_at`] and [`Self::go_to`].
+    pub fn go_to_time(&mut self, time: SystemTime) -> bool {
+        self.go_to(self.state_at(time))
+    }
+}
+
+impl<T: Clone + Default, S: UndoStorage<T>> Default for UndoWrapper<T, S> {
+    fn default() -> Self {
+        Self::with_storage(T::default())
+    }
+}
+
+impl<T: Clone, S: UndoStorage<T>> Deref for UndoWrapper<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.state(), &0);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+    }
+
+    #[test]
+    fn try_modify() {
+        let mut undo = UndoWrapper::new(0i32);
+
+        assert_eq!(
+            undo.try_modify("change", |x| {
+                *x += 1;
+                Ok::<_, ()>(*x)
+            }),
+            Ok(1)
+        );
+        assert_eq!(undo.state(), &1);
+        assert_eq!(undo.undo_count(), 1);
+
+        assert_eq!(
+            undo.try_modify("change", |x| {
+                *x += 1;
+                Err::<(), _>("failed")
+            }),
+            Err("failed")
+        );
+        assert_eq!(undo.state(), &1);
+        assert_eq!(undo.undo_count(), 1);
+    }
+
+    #[test]
+    fn failed_try_modify_keeps_redo_states() {
+        let mut undo = UndoWrapper::new(0i32);
+
+        undo.modify("change", |x| *x += 1);
+        undo.undo();
+
+        assert!(undo.try_modify("change", |_| Err::<(), _>(())).is_err());
+        assert_eq!(undo.redo_count(), 1);
+    }
+
+    #[test]
+    fn redo() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.state(), &0);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+
+        undo.redo();
+        assert_eq!(undo.state(), &1);
+    }
+
+    #[test]
+    fn redo_multiple() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.state(), &0);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &1);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &2);
+
+        undo.undo();
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+
+        undo.redo();
+        assert_eq!(undo.state(), &1);
+
+        undo.redo();
+        assert_eq!(undo.state(), &2);
+    }
+
+    #[test]
+    fn undo_redo_undo_redo() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.state(), &0);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+
+        undo.redo();
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+
+        undo.redo();
+        assert_eq!(undo.state(), &1);
+    }
+
+    #[test]
+    fn undo_count() {
+        let mut undo = UndoWrapper::new(());
+
+        assert_eq!(undo.undo_count(), 0);
+
+        undo.modify("change", |_| ());
+        assert_eq!(undo.undo_count(), 1);
+
+        undo.modify("change", |_| ());
+        assert_eq!(undo.undo_count(), 2);
+    }
+
+    #[test]
+    fn redo_count() {
+        let mut undo = UndoWrapper::new(());
+
+        assert_eq!(undo.redo_count(), 0);
+
+        undo.modify("change", |_| ());
+        undo.modify("change", |_| ());
+        assert_eq!(undo.redo_count(), 0);
+
+        undo.undo();
+        assert_eq!(undo.redo_count(), 1);
+
+        undo.undo();
+        assert_eq!(undo.redo_count(), 2);
+
+        undo.redo();
+        assert_eq!(undo.redo_count(), 1);
+
+        undo.redo();
+        assert_eq!(undo.redo_count(), 0);
+    }
+
+    #[test]
+    fn edit_clears_redo_states() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.state(), &0);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &1);
+
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.state(), &2);
+
+        undo.undo();
+        assert_eq!(undo.state(), &1);
+
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+
+        // push a completely new value. the redo states should be cleared.
+        undo.modify("change", |x| *x += 10);
+        assert_eq!(undo.state(), &10);
+
+        // doing redo now should not result in a previous value
+        assert!(!undo.redo());
+        assert_eq!(undo.state(), &10);
+        assert!(!undo.redo());
+        assert_eq!(undo.state(), &10);
+    }
+
+    #[test]
+    fn invalid_undo() {
+        let mut undo = UndoWrapper::new(());
+
+        undo.modify("change", |_| ());
+
+        assert!(undo.undo());
+        assert!(!undo.undo());
+        assert!(!undo.undo());
+    }
+
+    #[test]
+    fn invalid_redo() {
+        let mut undo = UndoWrapper::new(());
+
+        undo.modify("change", |_| ());
+        assert!(undo.undo());
+
+        assert!(undo.redo());
+        assert!(!undo.redo());
+        assert!(!undo.redo());
+    }
+
+    #[test]
+    fn can_undo_to_clean_state() {
+        let mut undo = UndoWrapper::new(());
+        assert!(undo.is_dirty());
+
+        undo.mark_clean();
+        assert!(!undo.is_dirty());
+
+        undo.modify("change", |_| ());
+        assert!(undo.is_dirty());
+
+        undo.undo();
+        assert!(!undo.is_dirty());
+
+        undo.redo();
+        assert!(undo.is_dirty());
+
+        undo.undo();
+        assert!(!undo.is_dirty());
+    }
+
+    #[test]
+    fn edit_wipes_future_clean_state() {
+        let mut undo = UndoWrapper::new(());
+        assert!(undo.is_dirty());
+
+        undo.modify("change", |_| ());
+        undo.mark_clean();
+        assert!(!undo.is_dirty());
+
+        undo.undo();
+        undo.modify("change", |_| ());
+        assert!(undo.is_dirty());
+    }
+
+    /// Stores the difference between 2 numbers, to test [`Deltas`].
+    impl Diff for i32 {
+        type Delta = Self;
+
+        fn diff(from: &Self, to: &Self) -> Self::Delta {
+            to - from
+        }
+
+        fn apply_delta(&mut self, delta: Self::Delta) -> Self::Delta {
+            *self += delta;
+            -delta
+        }
+    }
+
+    impl EstimateSize for i32 {
+        fn estimated_size(&self) -> usize {
+            std::mem::size_of::<Self>()
+        }
+    }
+
+    #[test]
+    fn max_steps_trims_oldest_states() {
+        let mut undo = UndoWrapper::new(0i32).with_max_steps(2);
+        undo.mark_clean();
+        for _ in 0..3 {
+            undo.modify("change", |x| *x += 1);
+        }
+        assert_eq!(undo.undo_count(), 2);
+
+        // the clean state was trimmed, so it can not be reached anymore
+        while undo.undo() {}
+        assert_eq!(undo.state(), &1);
+        assert!(undo.is_dirty());
+    }
+
+    #[test]
+    fn trimming_keeps_clean_state() {
+        let mut undo = UndoWrapper::new(0i32).with_max_steps(2);
+        undo.modify("change", |x| *x += 1);
+        undo.modify("change", |x| *x += 1);
+        undo.mark_clean();
+        undo.modify("change", |x| *x += 1);
+
+        undo.undo();
+        assert!(!undo.is_dirty());
+        assert_eq!(undo.state(), &2);
+    }
+
+    #[test]
+    fn max_bytes_keeps_most_recent_step() {
+        let mut undo = UndoWrapper::new(0i32).with_max_bytes(2 * std::mem::size_of::<i32>());
+        for _ in 0..5 {
+            undo.modify("change", |x| *x += 1);
+        }
+        assert_eq!(undo.undo_count(), 2);
+
+        let mut undo = UndoWrapper::new(0i32).with_max_bytes(0);
+        undo.modify("change", |x| *x += 1);
+        undo.modify("change", |x| *x += 1);
+        assert_eq!(undo.undo_count(), 1);
+    }
+
+    #[test]
+    fn deltas() {
+        let mut undo = UndoWrapper::<_, Deltas>::with_storage(0i32).with_max_steps(2);
+        undo.mark_clean();
+        undo.modify("change", |x| *x += 5);
+        assert_eq!(
+            undo.try_modify("change", |x| {
+                *x *= 3;
+                Ok::<_, ()>(*x)
+            }),
+            Ok(15)
+        );
+        assert!(undo
+            .try_modify("change", |x| {
+                *x += 1;
+                Err::<(), _>(())
+            })
+            .is_err());
+        assert_eq!(undo.state(), &15);
+
+        undo.undo();
+        assert_eq!(undo.state(), &5);
+        undo.undo();
+        assert_eq!(undo.state(), &0);
+        assert!(!undo.is_dirty());
+        undo.redo();
+        undo.redo();
+        assert_eq!(undo.state(), &15);
+
+        undo.modify("change", |x| *x -= 1);
+        assert_eq!(undo.undo_count(), 2);
+    }
+
+    #[test]
+    fn undo_tree_keeps_branches() {
+        let mut undo = UndoWrapper::new(0i32).with_undo_tree();
+        undo.modify("change", |x| *x += 1);
+        let first_branch = undo.current_id();
+        undo.undo();
+        undo.modify("change", |x| *x += 10);
+        undo.modify("change", |x| *x += 10);
+        assert_eq!(undo.branches(), [first_branch, undo.current_id()]);
+
+        // moving between branches goes through their common ancestor
+        let second_branch = undo.current_id();
+        assert!(undo.go_to(first_branch));
+        assert_eq!(undo.state(), &1);
+        assert_eq!(undo.undo_count(), 1);
+        assert!(undo.go_to(second_branch));
+        assert_eq!(undo.state(), &20);
+        assert_eq!(undo.undo_count(), 2);
+
+        // redo follows the branch that was visited last
+        undo.undo();
+        undo.undo();
+        assert_eq!(undo.redo_count(), 2);
+        undo.redo();
+        assert_eq!(undo.state(), &10);
+    }
+
+    #[test]
+    fn linear_history_drops_branches() {
+        let mut undo = UndoWrapper::new(0i32);
+        undo.modify("change", |x| *x += 1);
+        let dropped = undo.current_id();
+        undo.undo();
+        undo.modify("change", |x| *x += 10);
+
+        assert_eq!(undo.branches(), [undo.current_id()]);
+        assert!(!undo.go_to(dropped));
+        assert_eq!(undo.history().count(), 2);
+    }
+
+    #[test]
+    fn go_to_time() {
+        let mut undo = UndoWrapper::new(0i32).with_undo_tree();
+        undo.modify("change", |x| *x += 1);
+        undo.modify("change", |x| *x += 1);
+        let history = undo
+            .history()
+            .map(|state| (state.id, state.created))
+            .collect::<Vec<_>>();
+
+        assert!(undo.go_to_time(history[1].1));
+        assert_eq!(undo.state(), &1);
+        assert_eq!(undo.ancestors(undo.current_id()).count(), 2);
+
+        // times before the oldest state go to the oldest state
+        let long_ago = history[0].1 - std::time::Duration::from_secs(600);
+        assert_eq!(undo.state_at(long_ago), history[0].0);
+        assert!(undo.go_to_time(long_ago));
+        assert_eq!(undo.state(), &0);
+    }
+
+    #[test]
+    fn trimming_undo_tree_drops_oldest_branches_first() {
+        let mut undo = UndoWrapper::<_, Deltas>::with_storage(0i32)
+            .with_undo_tree()
+            .with_max_steps(3);
+        undo.modify("change", |x| *x += 1);
+        undo.undo();
+        undo.modify("change", |x| *x += 10);
+        undo.modify("change", |x| *x += 10);
+        undo.modify("change", |x| *x += 10);
+
+        // the abandoned branch is older than the current path, so it is dropped first
+        assert_eq!(undo.history().count(), 4);
+        assert_eq!(undo.branches(), [undo.current_id()]);
+        assert_eq!(undo.undo_count(), 3);
+        while undo.undo() {}
+        assert_eq!(undo.state(), &0);
+    }
+
+    #[test]
+    fn descriptions() {
+        let mut undo = UndoWrapper::new(0i32);
+        assert_eq!(undo.undo_description(), None);
+
+        undo.modify("Add one", |x| *x += 1);
+        undo.modify("Add two", |x| *x += 2);
+        assert_eq!(undo.undo_description(), Some("Add two"));
+
+        undo.undo();
+        assert_eq!(undo.undo_description(), Some("Add one"));
+        assert_eq!(undo.redo_description(), Some("Add two"));
+
+        let descriptions = undo.history().map(|state| state.description);
+        assert_eq!(descriptions.collect::<Vec<_>>(), ["", "Add one", "Add two"]);
+    }
+
+    #[test]
+    fn transaction_is_one_step() {
+        let mut undo = UndoWrapper::<_, Deltas>::with_storage(0i32);
+        undo.modify("Add one", |x| *x += 1);
+
+        let ret = undo.transaction("Add three", |undo| {
+            undo.modify("Add one", |x| *x += 1);
+            assert!(undo.try_modify("Fail", |_| Err::<(), _>(())).is_err());
+            undo.transaction("Add two", |undo| undo.modify("Add two", |x| *x += 2));
+
+            assert!(!undo.undo());
+            5
+        });
+        assert_eq!(ret, 5);
+        assert_eq!(undo.state(), &4);
+        assert_eq!(undo.undo_count(), 2);
+        assert_eq!(undo.undo_description(), Some("Add three"));
+
+        undo.undo();
+        assert_eq!(undo.state(), &1);
+
+        // transactions without changes are not recorded
+        undo.transaction("Nothing", |_| ());
+        assert_eq!(undo.redo_count(), 1);
+    }
+}